@@ -0,0 +1,275 @@
+use battleship::theme::Theme;
+use battleship::{Difficulty, PlacementStyle, Rules, TimeControl, Topology};
+use std::{path::PathBuf, time::Duration};
+
+///
+/// How many games sim plays at each level if no number is given
+///
+pub const DEFAULT_SIM_GAMES: usize = 100;
+
+///
+/// The help text printed by --help
+///
+pub const USAGE: &str = "\
+Usage: battleship [COMMAND] [OPTIONS]
+
+Commands:
+    play              Play against the computer (the default)
+    sim [GAMES]       Play GAMES simulated games at each level and print the average
+                      shots to win (100 if not given)
+    replay FILE       Play the game scripted in FILE and print its transcript
+    stats             Print the leaderboard and every player's record
+
+Options:
+    --difficulty LEVEL    beginner, easy, medium, hard or nightmare, instead of being asked
+    --placement STYLE     random or adversarial, instead of being asked
+    --rules RULES         optional rules, separated by commas: advanced (special weapons),
+                          sonar (one sonar scan each, or sonar=N for another scan
+                          N turns after each one), mobile (ships can move),
+                          mines (mines and decoys), layers (a deep layer for submarines),
+                          repair (repair a hit instead of firing)
+    --seed N              fix every random choice, so the same input plays the same game
+    --theme THEME         emoji or ascii
+    --board BOARD         square, or hex for a board of hexagons
+    --fleet FLEET         the ships each side places: standard, or shapes for a plus,
+                          T and L shaped carrier, battleship and cruiser
+    --move-time SECONDS   the most time one turn can take, before a random shot is fired
+    --clock MINUTES       the time each side has for the whole game, shown by the boards
+    -h, --help            print this help
+";
+
+///
+/// What the program was asked to do
+///     Play: play interactive games
+///     Sim: play this many simulated games at each level
+///     Replay: play the game scripted in this file
+///     Stats: print the stats tables
+///     Help: print USAGE
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Play,
+    Sim(usize),
+    Replay(PathBuf),
+    Stats,
+    Help,
+}
+
+///
+/// Everything given on the command line
+/// difficulty and placement are None if the player should be asked for them
+/// seed is None if the game should be different every time
+/// rules are the optional rules turned on
+/// shaped is true if ships are shaped, with --fleet shapes
+/// topology is the board to play on
+/// time_control is the time limits, with --move-time and --clock
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    pub difficulty: Option<Difficulty>,
+    pub placement: Option<PlacementStyle>,
+    pub seed: Option<u64>,
+    pub theme: Theme,
+    pub rules: Rules,
+    pub shaped: bool,
+    pub topology: Topology,
+    pub time_control: TimeControl,
+}
+
+///
+/// Arguements:
+///     args: &[String] - the command line arguments, without the program name
+/// Returns what the program should do, or a message saying what was wrong with
+/// the arguments.
+///
+/// The older forms --benchmark N and --script FILE still work, as sim N and replay FILE
+///
+pub fn parse(args: &[String]) -> Result<Cli, String> {
+    let mut cli = Cli {
+        command: Command::Play,
+        difficulty: None,
+        placement: None,
+        seed: None,
+        theme: Theme::default(),
+        rules: Rules::default(),
+        shaped: false,
+        topology: Topology::default(),
+        time_control: TimeControl::default(),
+    };
+    let mut command: Option<Command> = None;
+    let mut help = false;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .map(|value| value.to_string())
+                .ok_or(format!("{name} needs a value"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => help = true,
+            "--difficulty" => {
+                let level = value(arg)?;
+                cli.difficulty = Some(Difficulty::from_input(&level).ok_or(
+                    "--difficulty must be one of: beginner, easy, medium, hard, nightmare",
+                )?);
+            }
+            "--placement" => {
+                let style = value(arg)?;
+                cli.placement = Some(
+                    PlacementStyle::from_input(&style)
+                        .ok_or("--placement must be one of: random, adversarial")?,
+                );
+            }
+            "--rules" => {
+                let rules = value(arg)?;
+                cli.rules = Rules::from_input(&rules).map_err(|e| format!("--rules: {e}"))?;
+            }
+            "--seed" => {
+                let seed = value(arg)?;
+                cli.seed = Some(seed.parse().map_err(|_| "--seed must be a number")?);
+            }
+            "--theme" => {
+                let theme = value(arg)?;
+                cli.theme =
+                    Theme::from_input(&theme).ok_or("--theme must be one of: emoji, ascii")?;
+            }
+            "--board" => {
+                let board = value(arg)?;
+                cli.topology =
+                    Topology::from_input(&board).ok_or("--board must be one of: square, hex")?;
+            }
+            "--fleet" => {
+                let fleet = value(arg)?;
+                cli.shaped = match fleet.trim().to_lowercase().as_str() {
+                    "standard" => false,
+                    "shapes" => true,
+                    _ => return Err("--fleet must be one of: standard, shapes".to_string()),
+                };
+            }
+            "--move-time" => {
+                let seconds: u64 = value(arg)?
+                    .parse()
+                    .map_err(|_| "--move-time must be a number of seconds")?;
+                if seconds == 0 {
+                    return Err("--move-time must be at least 1 second".to_string());
+                }
+                cli.time_control.per_move = Some(Duration::from_secs(seconds));
+            }
+            "--clock" => {
+                let minutes: u64 = value(arg)?
+                    .parse()
+                    .map_err(|_| "--clock must be a number of minutes")?;
+                if minutes == 0 {
+                    return Err("--clock must be at least 1 minute".to_string());
+                }
+                cli.time_control.total = Some(Duration::from_secs(60 * minutes));
+            }
+            "--benchmark" => {
+                let games = value(arg)?;
+                let games = games
+                    .parse()
+                    .map_err(|_| "--benchmark needs the number of games to play")?;
+                command = Some(Command::Sim(games));
+            }
+            "--script" => command = Some(Command::Replay(PathBuf::from(value(arg)?))),
+            word if word.starts_with('-') => return Err(format!("unknown option {word}")),
+            word if command.is_some() => return Err(format!("unexpected argument {word}")),
+            "play" => command = Some(Command::Play),
+            "stats" => command = Some(Command::Stats),
+            "sim" => {
+                let games = match args.as_slice().first() {
+                    Some(games) if !games.starts_with('-') => {
+                        args.next();
+                        games
+                            .parse()
+                            .map_err(|_| "sim: the number of games must be a number")?
+                    }
+                    _ => DEFAULT_SIM_GAMES,
+                };
+                command = Some(Command::Sim(games));
+            }
+            "replay" => {
+                let file = value(arg)?;
+                command = Some(Command::Replay(PathBuf::from(file)));
+            }
+            word => return Err(format!("unknown command {word}")),
+        }
+    }
+
+    if cli.shaped && cli.topology == Topology::Hex {
+        return Err("--fleet shapes can only be played on the square board".to_string());
+    }
+    if cli.shaped && cli.rules.layers {
+        return Err("--fleet shapes can't be played with the layers rule".to_string());
+    }
+    if help {
+        cli.command = Command::Help;
+    } else if let Some(command) = command {
+        cli.command = command;
+    }
+    Ok(cli)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cli = parse(&args("")).unwrap();
+        assert_eq!(cli.command, Command::Play);
+        assert_eq!(cli.difficulty, None);
+
+        let cli = parse(&args("play --difficulty hard --seed 7 --theme ascii")).unwrap();
+        assert_eq!(cli.difficulty, Some(Difficulty::Hard));
+        assert_eq!(cli.seed, Some(7));
+        assert_eq!(cli.theme, Theme::Ascii);
+        assert!(parse(&args("--rules advanced")).unwrap().rules.advanced);
+        assert!(parse(&args("--rules sonar")).unwrap().rules.sonar);
+        assert!(parse(&args("--rules sonar,mobile")).unwrap().rules.mobile);
+        assert!(parse(&args("--rules mines")).unwrap().rules.mines);
+        assert!(parse(&args("--rules layers")).unwrap().rules.layers);
+        assert!(parse(&args("--rules repair")).unwrap().rules.repair);
+        assert!(parse(&args("--rules lasers")).is_err());
+
+        assert_eq!(parse(&args("sim")).unwrap().command, Command::Sim(100));
+        assert_eq!(
+            parse(&args("sim 20 --difficulty 1")).unwrap().command,
+            Command::Sim(20)
+        );
+        assert_eq!(
+            parse(&args("--benchmark 5")).unwrap().command,
+            Command::Sim(5)
+        );
+        assert_eq!(
+            parse(&args("replay game.txt")).unwrap().command,
+            Command::Replay(PathBuf::from("game.txt"))
+        );
+        assert_eq!(parse(&args("stats --help")).unwrap().command, Command::Help);
+        assert_eq!(parse(&args("-h stats")).unwrap().command, Command::Help);
+        assert!(parse(&args("--fleet standard")).is_ok());
+        assert!(parse(&args("--fleet shapes")).unwrap().shaped);
+        assert_eq!(parse(&args("--board hex")).unwrap().topology, Topology::Hex);
+        assert!(parse(&args("--board hex --fleet shapes")).is_err());
+        assert!(parse(&args("--rules layers --fleet shapes")).is_err());
+        let cli = parse(&args("--move-time 30 --clock 10")).unwrap();
+        assert_eq!(cli.time_control.per_move, Some(Duration::from_secs(30)));
+        assert_eq!(cli.time_control.total, Some(Duration::from_secs(600)));
+        assert!(parse(&args("--move-time 0")).is_err());
+        assert!(parse(&args("--clock soon")).is_err());
+
+        assert!(parse(&args("--board-size 10")).is_err());
+        assert!(parse(&args("host")).is_err());
+        assert!(parse(&args("--fleet tiny")).is_err());
+        assert!(parse(&args("--difficulty impossible")).is_err());
+        assert!(parse(&args("--seed")).is_err());
+        assert!(parse(&args("fly")).is_err());
+        assert!(parse(&args("stats play")).is_err());
+    }
+}
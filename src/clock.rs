@@ -0,0 +1,284 @@
+use crate::grid::Grid;
+use crate::layers::Layer;
+use crate::possible_guess::Guesser;
+use crate::rng::random;
+use std::{
+    fmt::{self, Display},
+    io,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+///
+/// The lines typed on the keyboard, read on their own thread so a turn can stop waiting
+/// for them when its time is up
+///
+static KEYBOARD: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+
+///
+/// The time each side has to play
+/// per_move: the most time one turn can take, if there is a limit
+/// total: the time each side has for the whole game, if there is a limit
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeControl {
+    pub per_move: Option<Duration>,
+    pub total: Option<Duration>,
+}
+
+impl TimeControl {
+    ///
+    /// Returns true if there is any time limit
+    ///
+    pub fn is_on(&self) -> bool {
+        self.per_move.is_some() || self.total.is_some()
+    }
+}
+
+///
+/// One side's clock, for a game with time limits
+/// control is the time limits it runs under
+/// left is the time left on the game clock, if there is one
+/// used is the time spent on every turn so far
+/// started is when the turn being timed started, if one is
+/// It is drawn as the time left on the game clock, or the time used if there is no game
+/// clock, then the limit for one turn if there is one, like 4:05 left, 30s a move
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    pub control: TimeControl,
+    pub left: Option<Duration>,
+    pub used: Duration,
+    started: Option<Instant>,
+}
+
+impl Clock {
+    ///
+    /// Creates a clock with all of the game's time left
+    ///
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            left: control.total,
+            used: Duration::ZERO,
+            started: None,
+        }
+    }
+
+    ///
+    /// Starts timing a turn
+    ///
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    ///
+    /// Stops timing the turn, taking the time it took off the game clock
+    ///
+    pub fn stop(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.spend(started.elapsed());
+        }
+    }
+
+    ///
+    /// Takes the time off the game clock, and adds it to the time used
+    ///
+    pub fn spend(&mut self, time: Duration) {
+        self.used += time;
+        self.left = self.left.map(|left| left.saturating_sub(time));
+    }
+
+    ///
+    /// Returns true if there was a game clock and it has run out
+    ///
+    pub fn is_out(&self) -> bool {
+        self.left == Some(Duration::ZERO)
+    }
+
+    ///
+    /// Returns how long the turn being timed can take: the limit for one turn or the time
+    /// left on the game clock, whichever is shorter, or None if there is no limit
+    ///
+    pub fn turn_limit(&self) -> Option<Duration> {
+        match (self.control.per_move, self.left) {
+            (Some(per_move), Some(left)) => Some(per_move.min(left)),
+            (per_move, left) => per_move.or(left),
+        }
+    }
+}
+
+impl Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes =
+            |time: Duration| format!("{}:{:02}", time.as_secs() / 60, time.as_secs() % 60);
+        match self.left {
+            Some(left) => write!(f, "{} left", minutes(left))?,
+            None => write!(f, "{} used", minutes(self.used))?,
+        }
+        if let Some(per_move) = self.control.per_move {
+            write!(f, ", {}s a move", per_move.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Returns true once the keyboard is read by read_keyboard, since the thread reading it
+/// may already be waiting for the next line
+///
+pub fn is_reading() -> bool {
+    KEYBOARD.get().is_some()
+}
+
+///
+/// Returns the lines typed on the keyboard, starting the thread that reads them the
+/// first time
+///
+fn keyboard() -> &'static Mutex<Receiver<String>> {
+    KEYBOARD.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(read) if read > 0 => {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        });
+        Mutex::new(receiver)
+    })
+}
+
+///
+/// Reads one line from the keyboard, for games with time limits
+/// If the deadline passes first, says so and returns the line given with it instead.
+/// The deadline is used up then, so the line is only played once.
+/// Arguements:
+///     deadline: &mut Option<(Instant, String)> - when the user's turn runs out of time,
+///         and the line to play for them if it does, or None for no limit
+/// Returns None if there is no input left
+///
+pub fn read_keyboard(deadline: &mut Option<(Instant, String)>) -> Option<String> {
+    let keyboard = keyboard().lock().expect("error: unable to read input");
+    let Some((until, _)) = deadline else {
+        return keyboard.recv().ok();
+    };
+    match keyboard.recv_timeout(until.saturating_duration_since(Instant::now())) {
+        Ok(line) => Some(line),
+        Err(RecvTimeoutError::Timeout) => {
+            let (_, fallback) = deadline.take()?;
+            println!("Time's up! Firing at {fallback}");
+            Some(fallback)
+        }
+        Err(RecvTimeoutError::Disconnected) => None,
+    }
+}
+
+impl Grid {
+    ///
+    /// Returns the (row, column) and layer of every point on the board that can still
+    /// be guessed, on every layer it has
+    ///
+    pub(crate) fn untried(&self) -> Vec<((i32, char), Layer)> {
+        let mut points = vec![];
+        for layer in self.layers() {
+            for (r, row) in self.grid[layer.index()].iter().enumerate() {
+                for (c, point) in row.iter().enumerate() {
+                    if self.can_guess(point.state) {
+                        points.push(((r as i32 + 1, (b'A' + c as u8) as char), layer));
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    ///
+    /// Returns a point on the board that can still be guessed and its layer, picked at
+    /// random, or None if there are none left
+    ///
+    pub(crate) fn random_untried(&self) -> Option<((i32, char), Layer)> {
+        let points = self.untried();
+        if points.is_empty() {
+            return None;
+        }
+        Some(points[(random::<f32>() * points.len() as f32).floor() as usize])
+    }
+
+    ///
+    /// The computer's guess once its game clock has run out: a point that hasn't been
+    /// guessed, picked at random. The guesser is still told the report.
+    /// Returns true if the game is over
+    ///
+    pub(crate) fn make_hurried_guess(&mut self, guesser: &mut dyn Guesser) -> bool {
+        // the computer's guesser only plays the surface
+        let points: Vec<(i32, char)> = self
+            .untried()
+            .into_iter()
+            .filter(|(_, layer)| *layer == Layer::Surface)
+            .map(|(point, _)| point)
+            .collect();
+        if points.is_empty() {
+            return false;
+        }
+        let (row, col) = points[(random::<f32>() * points.len() as f32).floor() as usize];
+        if !self.quiet {
+            println!("The computer is out of time");
+            println!("Computer guess: {col}{row}");
+        }
+        let report = self.guess(row, col);
+        guesser.record_guess(report, col, row);
+        report.1 .0 == 'f'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock() {
+        let control = TimeControl {
+            per_move: Some(Duration::from_secs(30)),
+            total: Some(Duration::from_secs(300)),
+        };
+        let mut clock = Clock::new(control);
+        assert_eq!(clock.to_string(), "5:00 left, 30s a move");
+        assert_eq!(clock.turn_limit(), Some(Duration::from_secs(30)));
+        clock.spend(Duration::from_secs(285));
+        assert_eq!(clock.turn_limit(), Some(Duration::from_secs(15)));
+        assert!(!clock.is_out());
+        clock.spend(Duration::from_secs(20));
+        assert!(clock.is_out());
+        assert_eq!(clock.used, Duration::from_secs(305));
+
+        let clock = Clock::new(TimeControl::default());
+        assert!(!clock.control.is_on());
+        assert_eq!(clock.turn_limit(), None);
+        assert_eq!(clock.to_string(), "0:00 used");
+
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.guess(1, 'A');
+        assert_eq!(grid.untried().len(), 99);
+        assert!(!grid.untried().contains(&((1, 'A'), Layer::Surface)));
+        assert!(grid.random_untried().is_some());
+
+        // a miss can be fired at again once ships move, and deep points count too
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.mobile = true;
+        grid.add_deep_layer();
+        grid.guess(1, 'A');
+        assert_eq!(grid.untried().len(), 200);
+        assert!(grid.untried().contains(&((10, 'J'), Layer::Deep)));
+    }
+}
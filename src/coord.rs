@@ -1,11 +1,18 @@
+use crate::clock;
+use crate::script;
+use crate::theme;
 use std::{
     char,
     fmt::{self, Display},
-    io,
+    io, process,
+    time::Instant,
 };
 
 ///
-/// Four states of a coordinate
+/// The states of a coordinate
+/// Mine and Decoy are only drawn on their owner's board, and Detonated is a mine
+/// that has been shot, with the mines rule
+/// Uncertain is a hit that may have been repaired, with the repair rule
 ///
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CoordState {
@@ -13,6 +20,10 @@ pub enum CoordState {
     Hit,
     Miss,
     Ship,
+    Mine,
+    Decoy,
+    Detonated,
+    Uncertain,
 }
 
 ///
@@ -24,19 +35,20 @@ pub struct Coordinate {
 }
 
 ///
-/// Different color display for each state of coordinate
+/// Different color display for each state of coordinate, from the current theme
 ///
 impl Display for Coordinate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.state {
-            CoordState::Blank => write!(f, "🟦"),
-            CoordState::Hit => write!(f, "🟥"),
-            CoordState::Miss => write!(f, "🟩"),
-            CoordState::Ship => write!(f, "🟨"),
-        }
+        write!(f, "{}", theme::current().symbol(self.state))
     }
 }
 
+///
+/// How a ship point that the other player never found is drawn when a
+/// board is revealed at the end of a game, in the emoji theme
+///
+pub const HIDDEN_SHIP: &str = "⬜";
+
 impl Coordinate {
     pub fn new() -> Self {
         Self {
@@ -74,7 +86,65 @@ pub fn get_coord_index(row: i32, column: char) -> (usize, usize) {
 }
 
 ///
-/// Gets user input and checks it for validity.
+/// Arguements:
+///     coord: (i32, char) - a (row, column)
+/// Returns true if the row is 1-10 and the column is A-J
+///
+pub fn on_board(coord: (i32, char)) -> bool {
+    let (row, column) = coord;
+    (1..=10).contains(&row) && ('A'..='J').contains(&column)
+}
+
+///
+/// Reads one line of user input, from the running script if there is one,
+/// otherwise from the keyboard, with no time limit
+/// Quits the game once there is no input left, instead of asking forever
+///
+pub fn read_input_line() -> String {
+    read_timed_line(&mut None)
+}
+
+///
+/// Reads one line of user input like read_input_line, but from a turn that can run out
+/// of time. Once a turn has had a time limit the keyboard is read by
+/// clock::read_keyboard, which gives back the deadline's fallback line if it passes.
+/// Arguements:
+///     deadline: &mut Option<(Instant, String)> - when the turn runs out of time and the
+///         line to play if it does, or None for no limit
+///
+pub fn read_timed_line(deadline: &mut Option<(Instant, String)>) -> String {
+    let line = if script::is_running() {
+        script::next_line()
+    } else if deadline.is_some() || clock::is_reading() {
+        clock::read_keyboard(deadline)
+    } else {
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
+            .expect("error: unable to read input");
+        (read > 0).then_some(line)
+    };
+    match line {
+        Some(line) => line,
+        None => {
+            println!("Out of input, quitting the game.");
+            process::exit(0);
+        }
+    }
+}
+
+///
+/// Gets user input and checks it for validity, using parse_coord
+///
+/// Return:
+///     ((row:i32, column:char), valid:bool)
+///
+pub fn get_input_coord() -> ((i32, char), bool) {
+    parse_coord(&read_input_line())
+}
+
+///
+/// Checks a coordinate typed by the user for validity.
 /// If it is a valid coordinate for the game, will return a tuple of the coord and true
 /// Otherwise returns an invalid tuple and false
 ///
@@ -88,19 +158,15 @@ pub fn get_coord_index(row: i32, column: char) -> (usize, usize) {
 /// Return:
 ///     ((row:i32, column:char), valid:bool)
 ///
-pub fn get_input_coord() -> ((i32, char), bool) {
+pub fn parse_coord(coord: &str) -> ((i32, char), bool) {
     let mut valid = true;
     let mut final_col = 'z';
     let mut final_row = 0;
 
     let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
 
-    let mut coord = String::new();
-    io::stdin()
-        .read_line(&mut coord)
-        .expect("error: unable to read input");
-
-    if !coord.trim().is_empty() {
+    let coord = coord.trim();
+    if !coord.is_empty() && coord.is_char_boundary(1) {
         let (col, row) = coord.split_at(1);
         let col: char = col
             .trim()
@@ -121,7 +187,7 @@ pub fn get_input_coord() -> ((i32, char), bool) {
         }
 
         let row = row.trim();
-        let mut is_num = true;
+        let mut is_num = !row.is_empty() && row.len() <= 2;
         for digit in row.chars() {
             if !digit.is_ascii_digit() {
                 is_num = false;
@@ -135,7 +201,7 @@ pub fn get_input_coord() -> ((i32, char), bool) {
         } else {
             valid = false;
         }
-        if final_row <= 0 || final_row > 11 {
+        if final_row <= 0 || final_row > 10 {
             println!("Invalid row: {final_row}");
             valid = false;
         }
@@ -150,7 +216,7 @@ pub fn get_input_coord() -> ((i32, char), bool) {
 
 #[cfg(test)]
 mod test {
-    use super::get_coord_index;
+    use super::{get_coord_index, parse_coord};
 
     #[test]
     fn test_get_coord_idx() {
@@ -158,4 +224,16 @@ mod test {
         assert_eq!(r, 5);
         assert_eq!(c, 9);
     }
+
+    #[test]
+    fn test_parse_coord() {
+        assert_eq!(parse_coord("a8\n"), ((8, 'A'), true));
+        assert_eq!(parse_coord(" J10 "), ((10, 'J'), true));
+        assert!(!parse_coord("K3").1);
+        assert!(!parse_coord("A11").1);
+        assert!(!parse_coord("A").1);
+        assert!(!parse_coord("").1);
+        assert!(!parse_coord("undo").1);
+        assert!(!parse_coord("🟦3").1);
+    }
 }
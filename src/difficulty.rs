@@ -0,0 +1,193 @@
+use crate::monte_carlo::MonteCarloGuesser;
+use crate::possible_guess::{Guesser, PossibleGuesses};
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+///
+/// How many layouts the Monte Carlo opponent samples for each guess by default
+///
+pub const DEFAULT_SAMPLES: usize = 2000;
+
+///
+/// How long, in milliseconds, the Monte Carlo opponent samples for each guess by default
+///
+pub const DEFAULT_TIME_BUDGET_MS: u64 = 250;
+
+///
+/// The named levels the computer can play at, from weakest to strongest
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Beginner,
+    #[default]
+    Easy,
+    Medium,
+    Hard,
+    Nightmare,
+}
+
+///
+/// The strategy configuration the computer guesses with
+/// targeting: after a hit, guess the points around it until the ship is sunk
+/// parity: while hunting, only guess points on the parity spacing of the
+///     shortest ship that is still afloat
+/// density: while hunting, guess the point that the most remaining ship
+///     placements could cover
+/// hunt_cutoff: once this many guesses have been made, stop using parity and
+///     density and hunt randomly. None never stops.
+/// monte_carlo: use the MonteCarloGuesser instead of PossibleGuesses
+/// samples: the most layouts the MonteCarloGuesser samples for each guess
+/// time_budget_ms: the most milliseconds the MonteCarloGuesser samples for each guess
+/// player_bias: bias hunting towards where the player has put ships in past games
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AiSettings {
+    pub targeting: bool,
+    pub parity: bool,
+    pub density: bool,
+    pub hunt_cutoff: Option<i32>,
+    pub monte_carlo: bool,
+    pub samples: usize,
+    pub time_budget_ms: u64,
+    pub player_bias: bool,
+}
+
+impl AiSettings {
+    ///
+    /// Builds the computer opponent these settings describe
+    ///
+    pub fn opponent(&self) -> Box<dyn Guesser> {
+        if self.monte_carlo {
+            Box::new(MonteCarloGuesser::new(
+                self.samples,
+                Duration::from_millis(self.time_budget_ms),
+            ))
+        } else {
+            let mut possible_guesses = PossibleGuesses::new();
+            possible_guesses.settings = *self;
+            Box::new(possible_guesses)
+        }
+    }
+}
+
+impl Difficulty {
+    ///
+    /// Every level, in the order they are shown in the menu
+    ///
+    pub fn all() -> Vec<Difficulty> {
+        vec![
+            Difficulty::Beginner,
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+            Difficulty::Nightmare,
+        ]
+    }
+
+    ///
+    /// Maps each level to the strategy it plays with
+    ///     Beginner: purely random guesses
+    ///     Easy: random hunting, and targeting after a hit
+    ///     Medium: parity hunting, and targeting after a hit
+    ///     Hard: parity hunting on the highest density points, and targeting after a hit
+    ///     Nightmare: Monte Carlo sampling of every layout that fits what has been seen
+    /// Hard and Nightmare also learn where the player likes to put their ships
+    ///
+    pub fn settings(&self) -> AiSettings {
+        let beginner = AiSettings {
+            targeting: false,
+            parity: false,
+            density: false,
+            hunt_cutoff: None,
+            monte_carlo: false,
+            samples: DEFAULT_SAMPLES,
+            time_budget_ms: DEFAULT_TIME_BUDGET_MS,
+            player_bias: false,
+        };
+        match self {
+            Difficulty::Beginner => beginner,
+            Difficulty::Easy => AiSettings {
+                targeting: true,
+                ..beginner
+            },
+            Difficulty::Medium => AiSettings {
+                targeting: true,
+                parity: true,
+                ..beginner
+            },
+            Difficulty::Hard => AiSettings {
+                targeting: true,
+                parity: true,
+                density: true,
+                player_bias: true,
+                ..beginner
+            },
+            Difficulty::Nightmare => AiSettings {
+                monte_carlo: true,
+                player_bias: true,
+                ..beginner
+            },
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     input: &str - either the level's name or its number in the menu (starting at 1)
+    /// Returns the matching level, or None if the input is not a level.
+    /// Names are not case sensitive.
+    ///
+    pub fn from_input(input: &str) -> Option<Difficulty> {
+        let input = input.trim().to_lowercase();
+        let levels = Difficulty::all();
+
+        if let Ok(number) = input.parse::<usize>() {
+            if (1..=levels.len()).contains(&number) {
+                return Some(levels[number - 1]);
+            }
+            return None;
+        }
+        levels
+            .into_iter()
+            .find(|level| level.to_string().to_lowercase() == input)
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Beginner => f.pad("Beginner"),
+            Difficulty::Easy => f.pad("Easy"),
+            Difficulty::Medium => f.pad("Medium"),
+            Difficulty::Hard => f.pad("Hard"),
+            Difficulty::Nightmare => f.pad("Nightmare"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_input() {
+        assert_eq!(Difficulty::from_input("1"), Some(Difficulty::Beginner));
+        assert_eq!(Difficulty::from_input("4\n"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_input("medium"), Some(Difficulty::Medium));
+        assert_eq!(Difficulty::from_input("EASY"), Some(Difficulty::Easy));
+        assert_eq!(Difficulty::from_input("5"), Some(Difficulty::Nightmare));
+        assert_eq!(Difficulty::from_input("0"), None);
+        assert_eq!(Difficulty::from_input("impossible"), None);
+    }
+
+    #[test]
+    fn test_settings() {
+        assert!(!Difficulty::Beginner.settings().targeting);
+        assert!(Difficulty::Easy.settings().targeting);
+        assert!(!Difficulty::Easy.settings().parity);
+        assert!(Difficulty::Medium.settings().parity);
+        assert!(Difficulty::Hard.settings().density);
+        assert!(Difficulty::Nightmare.settings().monte_carlo);
+    }
+}
@@ -1,34 +1,122 @@
+use crate::clock::{Clock, TimeControl};
+use crate::coord::{get_coord_index, read_input_line, CoordState};
+use crate::difficulty::Difficulty;
 use crate::grid::Grid;
-use crate::possible_guess::PossibleGuesses;
-
-use std::io;
+use crate::layers::Layer;
+use crate::mines::Blast;
+use crate::placement::PlacementStyle;
+use crate::profile::PlayerProfile;
+use crate::rules::Rules;
+use crate::script;
+use crate::stats::{GameTally, StatsStore};
+use crate::summary::game_summary;
+use crate::theme;
+use crate::weapons::Arsenal;
+use std::time::Instant;
 
 ///
-/// Displays the game by displaying each grid using their Display function
+/// Displays the game by displaying each grid using their Display function, with each
+/// side's clock if there are time limits
 ///
-fn display_game(computer: &Grid, user: &Grid) {
-    println!("Computer: ");
+fn display_game(computer: &Grid, user: &Grid, clocks: Option<(&Clock, &Clock)>) {
+    let (computer_clock, user_clock) = clocks.map_or((String::new(), String::new()), |(c, u)| {
+        (c.to_string(), u.to_string())
+    });
+    println!("Computer: {computer_clock}");
     println!("{}", computer);
-    println!("You: ");
+    println!("You: {user_clock}");
     println!("{}", user);
 }
 
+///
+/// Returns true if any guess at the grid since it had this many guesses hit a mine
+///
+fn hit_mine(grid: &Grid, shots: usize) -> bool {
+    grid.history[shots..]
+        .iter()
+        .any(|shot| shot.report.0 == 'x')
+}
+
+///
+/// Shows a menu of the difficulty levels and asks the user to pick one,
+/// either by number or by name.
+/// Loops until a valid level is given
+/// Returns the chosen Difficulty
+///
+fn request_difficulty() -> Difficulty {
+    println!("What level of difficulty do you want?");
+    for (i, level) in Difficulty::all().iter().enumerate() {
+        println!("  {}: {level}", i + 1);
+    }
+    loop {
+        let diff = read_input_line();
+        match Difficulty::from_input(&diff) {
+            Some(level) => return level,
+            None => println!("Please enter the number or name of a level"),
+        }
+    }
+}
+
+///
+/// Shows a menu of the ways the computer can place its ships and asks the user
+/// to pick one, either by number or by name.
+/// Loops until a valid style is given
+/// Returns the chosen PlacementStyle
+///
+fn request_placement() -> PlacementStyle {
+    println!("How should the computer place its ships?");
+    for (i, style) in PlacementStyle::all().iter().enumerate() {
+        println!("  {}: {style}", i + 1);
+    }
+    loop {
+        let style = read_input_line();
+        match PlacementStyle::from_input(&style) {
+            Some(style) => return style,
+            None => println!("Please enter the number or name of a placement style"),
+        }
+    }
+}
+
 ///
 /// Driver for the whole program
+/// Arguements:
+///     difficulty: Option<Difficulty> - the level chosen on the command line, if any.
+///         When it is None the user is asked for a level at the start of every game.
+///     placement: Option<PlacementStyle> - how the computer places its ships, if chosen
+///         on the command line. When it is None the user is asked at the start of every game.
+///     rules: Rules - the optional rules every game is played with
+///     time_control: TimeControl - the time limits every game is played with
+/// Asks the user for their name, and loads their profile so the computer can learn
+/// where they like to put their ships. No profile is kept if they leave it blank.
+/// When a script is running the game is always played as a guest, so nothing is saved.
 /// Calls the game function and will loop the game function
 /// until the user no longer wants a new game
 ///
-pub fn game_loop() {
+pub fn game_loop(
+    difficulty: Option<Difficulty>,
+    placement: Option<PlacementStyle>,
+    rules: Rules,
+    time_control: TimeControl,
+) {
+    let name = if script::is_running() {
+        String::new()
+    } else {
+        println!("What is your name? (Leave it blank to play as a guest)");
+        read_input_line()
+    };
+    let name = name.trim();
+
+    let mut profile = if name.is_empty() {
+        None
+    } else {
+        Some(PlayerProfile::load(&PlayerProfile::default_dir(), name))
+    };
+
     loop {
-        game();
+        game(difficulty, placement, rules, time_control, profile.as_mut());
         println!("Play again?\nPress 'y' for a new game, any other key to quit.");
-        let mut in_string = "".to_string();
-
-        io::stdin()
-            .read_line(&mut in_string)
-            .expect("error: unable to read input");
-        in_string = in_string.trim().to_string();
-        if in_string != *"y" {
+        let in_string = read_input_line();
+        if in_string.trim() != "y" {
             break;
         }
     }
@@ -37,41 +125,221 @@ pub fn game_loop() {
 ///
 /// Sets up a new game and then loops the guesses until the game is finished
 /// Creates a new computer_grid (the computer's gameboard), user_grid (the user's
-/// game board) and guessing_points (a reference for the computer to guide it's
-/// guesses).
-/// Asks the user how difficult they want the game
-/// Prints a endgame message
+/// game board) and guesser (the computer opponent for the chosen difficulty, which
+/// guides it's guesses).
+/// Asks the user how difficult they want the game, unless a difficulty was given
+/// Asks the user how the computer should place its ships, unless a style was given
+/// If there is a player profile and the level learns the player's habits, the
+/// guesser is given the profile's heatmap
+/// With the advanced, sonar or mobile rules, each side gets an Arsenal of special weapons,
+/// scans or moves
+/// With the mines rule, each side places mines and decoys, and a player who shoots a mine
+/// loses their next turn or gives away one of their ship points
+/// With the layers rule, each board gets a deep layer for the submarine, and the computer
+/// guesses at it with a second guesser of the same level
+/// With the repair rule, each side can repair a hit instead of firing. The other side is
+/// only told that a repair happened.
+/// With time limits, each side's turns are timed on its clock. When the user's turn runs
+/// out of time a random shot is fired for them, and once the computer's game clock runs
+/// out it only fires random shots
+/// Prints a endgame message and the game summary
+/// If the user lost, reveals where the computer's remaining ships were
+/// Records where the user put their ships in the profile and saves it
+/// Records the result and the user's shots in the stats store and saves it
 ///  
-fn game() {
+fn game(
+    difficulty: Option<Difficulty>,
+    placement: Option<PlacementStyle>,
+    rules: Rules,
+    time_control: TimeControl,
+    profile: Option<&mut PlayerProfile>,
+) {
     let mut computer_grid = Grid::new();
     let mut user_grid = Grid::new();
-    let mut guessing_points = PossibleGuesses::new();
-
-    println!("What level of difficulty do you want? Press 'h' for hard and 'e' for easy");
-    let mut diff = "".to_string();
-    io::stdin()
-        .read_line(&mut diff)
-        .expect("error: unable to read input");
-    let diff = diff.trim().to_string();
-    if diff == *"h" {
-        guessing_points.difficult = true;
+    for grid in [&mut computer_grid, &mut user_grid] {
+        grid.shaped = rules.shaped;
+        grid.topology = rules.topology;
     }
 
-    computer_grid.set_computer_ships();
+    let difficulty = match difficulty {
+        Some(level) => level,
+        None => request_difficulty(),
+    };
+    let placement = match placement {
+        Some(style) => style,
+        None => request_placement(),
+    };
+    let settings = difficulty.settings();
+    let mut guesser = settings.opponent();
+    guesser.play_by(rules);
+    if let Some(profile) = &profile {
+        if settings.player_bias && profile.games > 0 {
+            guesser.learn_habits(profile.heatmap());
+        }
+    }
+    let mut deep_guesser = settings.opponent();
+    deep_guesser.play_by(rules);
+    if rules.layers {
+        computer_grid.add_deep_layer();
+        user_grid.add_deep_layer();
+        guesser.expect_ships(&[5, 4, 3, 2]);
+        deep_guesser.expect_ships(&[3]);
+    }
+
+    computer_grid
+        .set_computer_ships(placement)
+        .expect("error: unable to place the computer's ships");
 
     user_grid.request_user_ships();
+    // where the user placed their ships, before any of them move
+    let placed = user_grid.fleet.clone();
+    if rules.mines {
+        user_grid.request_user_traps();
+        computer_grid.set_computer_traps();
+    }
+    let mut user_arsenal = Arsenal::for_rules(rules);
+    let mut computer_arsenal = Arsenal::for_rules(rules);
+    let specials = rules.advanced || rules.sonar || rules.mobile || rules.repair;
+    for grid in [&mut user_grid, &mut computer_grid] {
+        grid.mobile = rules.mobile;
+    }
+
+    // whether each player loses their next turn to a mine
+    let mut user_skips = false;
+    let mut computer_skips = false;
+
+    let timed = time_control.is_on();
+    let mut user_clock = Clock::new(time_control);
+    let mut computer_clock = Clock::new(time_control);
 
+    let won;
     loop {
-        display_game(&computer_grid, &user_grid);
-        let finished = computer_grid.request_user_guess();
+        display_game(
+            &computer_grid,
+            &user_grid,
+            timed.then_some((&computer_clock, &user_clock)),
+        );
+        let mut deadline = None;
+        if timed {
+            let until = user_clock.turn_limit().map(|limit| Instant::now() + limit);
+            let fallback = computer_grid
+                .random_untried()
+                .map(|((row, col), layer)| match layer {
+                    Layer::Surface => format!("{col}{row}"),
+                    Layer::Deep => format!("{col}{row} deep"),
+                });
+            deadline = until.zip(fallback);
+            user_clock.start();
+        }
+        // the user's turn only changes their own fleet if they moved a ship
+        let before = user_grid.fleet.clone();
+        let shots = computer_grid.history.len();
+        let repairs = user_arsenal.repairs;
+        let finished = if user_skips {
+            println!("You lose this turn to the mine");
+            user_skips = false;
+            false
+        } else if specials {
+            computer_grid.request_user_attack(&mut user_arsenal, &mut user_grid, &mut deadline)
+        } else {
+            computer_grid.request_user_guess(&mut deadline)
+        };
+        user_clock.stop();
+        if user_arsenal.repairs < repairs {
+            guesser.opponent_repaired();
+        } else if user_grid.fleet != before {
+            guesser.opponent_moved();
+            deep_guesser.opponent_moved();
+        }
         if finished {
             println!("Congratulations! You won!");
+            won = true;
             break;
         }
-        let finished = user_grid.make_computer_guess(&mut guessing_points);
+        if hit_mine(&computer_grid, shots) {
+            match user_grid.blast() {
+                Blast::LoseTurn => {
+                    println!("The mine costs you your next turn");
+                    user_skips = true;
+                }
+                Blast::Reveal((row, col)) => {
+                    println!("The mine gave away your ship at {col}{row}");
+                    guesser.reveal(col, row);
+                }
+            }
+        }
+
+        let user_shots = shots;
+        let shots = user_grid.history.len();
+        computer_clock.start();
+        let turn = if computer_skips {
+            println!("The computer loses this turn to the mine");
+            computer_skips = false;
+            Ok(false)
+        } else if computer_clock.is_out() {
+            Ok(user_grid.make_hurried_guess(guesser.as_mut()))
+        } else if computer_arsenal.repairs > 0 && computer_grid.computer_repair() {
+            computer_arsenal.repairs -= 1;
+            Ok(false)
+        } else if rules.mobile && computer_grid.evade(user_shots) {
+            Ok(false)
+        } else if rules.layers && user_grid.pick_layer() == Layer::Deep {
+            user_grid.make_deep_guess(deep_guesser.as_mut())
+        } else if specials {
+            user_grid.make_computer_attack(
+                guesser.as_mut(),
+                &mut computer_arsenal,
+                &computer_grid.fleet,
+            )
+        } else {
+            user_grid.make_computer_guess(guesser.as_mut())
+        };
+        // a guesser that has run out of points passes its turn rather than guessing again
+        let finished = turn.unwrap_or_else(|e| {
+            println!("The computer passes: {e}");
+            false
+        });
+        computer_clock.stop();
+        if !finished && hit_mine(&user_grid, shots) {
+            match computer_grid.blast() {
+                Blast::LoseTurn => {
+                    println!("The mine costs the computer its next turn");
+                    computer_skips = true;
+                }
+                Blast::Reveal((row, col)) => {
+                    println!("The mine gave away a computer ship at {col}{row}");
+                    let (r, c) = get_coord_index(row, col);
+                    computer_grid.grid[Layer::Surface.index()][r][c].state = CoordState::Ship;
+                }
+            }
+        }
         if finished {
             println!("Too bad, you lost. Try again?");
+            println!(
+                "The computer's ships still afloat ({} in its fleet below was never found):",
+                theme::current().hidden_ship()
+            );
+            for line in computer_grid.afloat_report() {
+                println!("  {line}");
+            }
+            won = false;
             break;
         }
     }
+    println!("{}", game_summary(&user_grid, &computer_grid));
+
+    if let Some(profile) = profile {
+        profile.record(&placed);
+        if let Err(e) = profile.save(&PlayerProfile::default_dir()) {
+            println!("Unable to save your profile: {e}");
+        }
+
+        let path = StatsStore::default_path();
+        let mut stats = StatsStore::load(&path);
+        let tally = GameTally::from_history(&computer_grid.history);
+        stats.record(&profile.name, difficulty, won, &tally);
+        if let Err(e) = stats.save(&path) {
+            println!("Unable to save your stats: {e}");
+        }
+    }
 }
@@ -1,28 +1,259 @@
 use crate::coord::*;
+use crate::layers::{parse_layered_coord, Layer};
+use crate::placement::*;
 use crate::possible_guess::*;
-use rand::random;
+use crate::rng::random;
+use crate::shapes::{shape_of, Shape};
+use crate::ship::{Fleet, ShipId};
+use crate::theme;
+use crate::topology::Topology;
 use std::{
     char,
-    collections::HashMap,
     fmt::{self, Display},
+    time::Instant,
 };
 
+///
+/// The fleet each side places, as (name, length), in the order ships are asked
+/// for and shown in. Largest first.
+///
+pub const FLEET: [(&str, u32); 5] = [
+    ("Carrier", 5),
+    ("Battleship", 4),
+    ("Cruiser", 3),
+    ("Submarine", 3),
+    ("Destroyer", 2),
+];
+
+///
+/// Arguements:
+///     name: &str - the name of a ship
+/// Returns where the ship comes in FLEET, for sorting ships into fleet order.
+/// Ships that aren't in FLEET go last.
+///
+pub fn fleet_position(name: &str) -> usize {
+    FLEET
+        .iter()
+        .position(|&(ship, _)| ship == name)
+        .unwrap_or(FLEET.len())
+}
+
+///
+/// Which way a ship runs from its starting coordinate
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    ///
+    /// Arguements:
+    ///     input: &str - a direction the user typed, not case sensitive
+    ///         h, horizontal, across, right or r for Right
+    ///         v, vertical, down or d for Down
+    ///         left or l for Left, up or u for Up
+    /// Returns the Direction, or None if it isn't one
+    ///
+    pub fn from_input(input: &str) -> Option<Direction> {
+        match input.trim().to_lowercase().as_str() {
+            "h" | "horizontal" | "across" | "right" | "r" => Some(Direction::Right),
+            "v" | "vertical" | "down" | "d" => Some(Direction::Down),
+            "left" | "l" => Some(Direction::Left),
+            "up" | "u" => Some(Direction::Up),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns how far one step in this direction moves, as (rows, columns)
+    ///
+    pub fn step(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     start: (i32, char) - the (row, column) the ship starts on
+    ///     size: u32 - the length of the ship
+    /// Returns the (row, column) the other end of the ship is on,
+    /// or None if the ship would run off the board
+    ///
+    pub fn end_point(&self, start: (i32, char), size: u32) -> Option<(i32, char)> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let (row, col) = start;
+        let col_idx = columns.iter().position(|&c| c == col)? as i32;
+        let length = size as i32 - 1;
+
+        let (end_row, end_col) = match self {
+            Direction::Up => (row - length, col_idx),
+            Direction::Down => (row + length, col_idx),
+            Direction::Left => (row, col_idx - length),
+            Direction::Right => (row, col_idx + length),
+        };
+        if (1..=10).contains(&end_row) && (0..10).contains(&end_col) {
+            Some((end_row, columns[end_col as usize]))
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// What the user entered while placing ships
+///     Coord: a valid coordinate (row, column)
+///     Heading: a valid starting coordinate and the direction the ship runs, like A1 down
+///     Anchor: a valid coordinate and the number of the way a shaped ship lies, like A1 2
+///     Undo: take back the last ship placed
+///     Clear: take back every ship
+///     Move: take back the named ship (lowercase) to place it again
+///     Invalid: anything else
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlacementCommand {
+    Coord((i32, char)),
+    Heading((i32, char), Direction),
+    Anchor((i32, char), usize),
+    Undo,
+    Clear,
+    Move(String),
+    Invalid,
+}
+
+///
+/// Arguements:
+///     input: &str - a line the user entered while placing ships
+/// Returns the command it is. Commands are not case sensitive.
+/// A column and a single number is read as a coordinate, even with spaces in it like A 8.
+/// Anything else with a space is read as a coordinate and a direction or number.
+///
+pub fn parse_placement_command(input: &str) -> PlacementCommand {
+    let command = input.trim().to_lowercase();
+    if command == "undo" {
+        return PlacementCommand::Undo;
+    }
+    if command == "clear" {
+        return PlacementCommand::Clear;
+    }
+    if let Some(ship) = command.strip_prefix("move ") {
+        return PlacementCommand::Move(ship.trim().to_string());
+    }
+    // a column then a single number is one coordinate, whatever spaces are in it
+    let rest: Vec<&str> = command.get(1..).unwrap_or("").split_whitespace().collect();
+    let whole = rest.len() == 1 && rest[0].chars().all(|c| c.is_ascii_digit());
+    if let Some((coord, direction)) = command.split_once(' ').filter(|_| !whole) {
+        if let Ok(orientation) = direction.trim().parse::<usize>() {
+            return match parse_coord(coord) {
+                (coord, true) => PlacementCommand::Anchor(coord, orientation),
+                (_, false) => PlacementCommand::Invalid,
+            };
+        }
+        return match (parse_coord(coord), Direction::from_input(direction)) {
+            ((coord, true), Some(direction)) => PlacementCommand::Heading(coord, direction),
+            _ => PlacementCommand::Invalid,
+        };
+    }
+    match parse_coord(input) {
+        (coord, true) => PlacementCommand::Coord(coord),
+        (_, false) => PlacementCommand::Invalid,
+    }
+}
+
+///
+/// One guess made against a Grid
+/// row: i32 - 1-10 - the row of the guess
+/// column: char - A-J - the column of the guess
+/// report: the report the guess returned, see Grid::guess
+/// ship: the id of the ship that was hit, if one was
+/// layer: the layer of the board the guess was aimed at
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shot {
+    pub row: i32,
+    pub column: char,
+    pub report: (char, (char, i32)),
+    pub ship: Option<ShipId>,
+    pub layer: Layer,
+}
+
+///
+/// What a guess did, without the codes used in Grid::guess's report
+///     Miss: nothing was hit, or the point had already been guessed
+///     Hit: a ship was hit but is still afloat
+///     StillHit: a hit left uncertain by a repair was fired at again, and is still hit
+///     Sunk: a ship of this length was sunk
+///     Won: a ship of this length was sunk, and it was the last one
+///     Mine: a mine was shot, with the mines rule
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Miss,
+    Hit,
+    StillHit,
+    Sunk(i32),
+    Won(i32),
+    Mine,
+}
+
+impl Outcome {
+    ///
+    /// Arguements:
+    ///     report: (char, (char, i32)) - a report in the format returned by Grid::guess
+    /// Returns the Outcome the report describes
+    ///
+    pub fn from_report(report: (char, (char, i32))) -> Outcome {
+        match report {
+            (_, ('f', length)) => Outcome::Won(length),
+            (_, ('s', length)) => Outcome::Sunk(length),
+            ('h', ('a', _)) => Outcome::StillHit,
+            ('h', _) => Outcome::Hit,
+            ('x', _) => Outcome::Mine,
+            _ => Outcome::Miss,
+        }
+    }
+}
+
 ///
 /// This holds the information about the game board and game pieces.
-/// The grid is the game board, the ships are the ships.
+/// The grid is the game board, indexed [layer][row][column]: just the surface, or the
+///     surface and then the deep layer with the layers rule, see the layers module
+/// The fleet is the ships, on every layer.
+/// Sunk ships stay in the fleet, with where they were placed and every hit on them.
+/// quiet stops guesses from printing their results, for simulated games.
+/// history is every guess made against this board, in order
+/// mobile lets points that were missed be guessed again, since in the "mobile fleet"
+///     rules a ship may have moved onto them
+/// mines and decoys are the (row, column) of every mine and decoy, with the mines rule
+/// shaped is true if ships take their pieces from SHAPED_FLEET, see the shapes module
+/// topology is the board's, which decides how it is drawn and which ways ships can lie
 ///
 #[derive(Clone, Debug)]
 pub struct Grid {
-    pub grid: Vec<Vec<Coordinate>>,
-    pub ships: HashMap<String, Vec<(usize, usize)>>,
+    pub grid: Vec<Vec<Vec<Coordinate>>>,
+    pub fleet: Fleet,
+    pub quiet: bool,
+    pub history: Vec<Shot>,
+    pub(crate) mobile: bool,
+    pub(crate) mines: Vec<(usize, usize)>,
+    pub(crate) decoys: Vec<(usize, usize)>,
+    pub(crate) shaped: bool,
+    pub(crate) topology: Topology,
 }
 
 impl Grid {
     ///
-    /// We set up the board with a two layer 10 X 10 vector of coordinates
+    /// We set up the board with one layer, a 10 X 10 vector of coordinates
     /// Coordinate is a struct in the coord file
     ///
-    /// The ships are set up as an empty hashmap that will be added to as
+    /// The ships are set up as an empty Fleet that will be added to as
     /// ships are legally placed.
     ///
     pub fn new() -> Self {
@@ -39,8 +270,15 @@ impl Grid {
             new_row = vec![];
         }
         Self {
-            grid: (new_grid),
-            ships: HashMap::new(),
+            grid: vec![new_grid],
+            fleet: Fleet::new(),
+            quiet: false,
+            history: vec![],
+            mobile: false,
+            mines: vec![],
+            decoys: vec![],
+            shaped: false,
+            topology: Topology::Square,
         }
     }
 
@@ -49,25 +287,43 @@ impl Grid {
     /// Arguements:
     ///     row: i32 - between 1-10 - the row of the coordinate that will change
     ///     column: char - between A - J - the column of the coordinate that will change
+    ///     layer: Layer - the layer of the board it is on
     /// blank, ship, hit, and miss
     ///
-    fn update_coord_state(&mut self, row: i32, column: char, new_state: CoordState) {
+    fn update_coord_state(&mut self, row: i32, column: char, layer: Layer, new_state: CoordState) {
         let (r_idx, c_idx) = get_coord_index(row, column);
 
-        self.grid[r_idx][c_idx].state = new_state;
+        self.grid[layer.index()][r_idx][c_idx].state = new_state;
+    }
+
+    ///
+    /// Prints a message, unless the grid is quiet
+    ///
+    fn say(&self, message: &str) {
+        if !self.quiet {
+            println!("{message}");
+        }
     }
 
     ///
     /// Driver to place all of the user's ships
     /// Set up to get the user input for ship placement.
-    /// Create a vec of possible ships points to make sure that ships are not placed on
-    /// top of each other.
-    /// Create a hashmap with ship names and their length
-    /// Pull each ship from the hashmap, and call self.request_ship on it
+    /// Create a vec of possible ships points for each layer to make sure that ships are not
+    /// placed on top of each other.
+    /// Create a queue of the ships still to be placed, in FLEET order
+    /// Pull each ship from the queue, and call self.request_ship on it, or
+    /// self.request_shaped_ship if it isn't straight. Ships that dive are placed on
+    /// the deep layer, if there is one, see layer_for.
+    /// Instead of a coordinate the user can enter
+    ///     undo - take back the last ship placed
+    ///     clear - take back every ship
+    ///     move <ship> - take back that ship and place it again next
+    /// Once every ship is placed the user has to confirm the fleet before play starts,
+    /// and can still use the commands above instead.
     /// Print the user board before beginning as a reference, and after each ship placement
     /// no return value
     ///
-    pub fn request_user_ships(&mut self) {
+    pub(crate) fn request_user_ships(&mut self) {
         let mut points = vec![];
         for i in 0..10 {
             for j in 0..10 {
@@ -75,21 +331,106 @@ impl Grid {
             }
         }
 
-        let mut possible_ships: HashMap<String, u32> = HashMap::new();
-        possible_ships.insert("Carrier".to_string(), 5);
-        possible_ships.insert("Battleship".to_string(), 4);
-        possible_ships.insert("Cruiser".to_string(), 3);
-        possible_ships.insert("Submarine".to_string(), 3);
-        possible_ships.insert("Destroyer".to_string(), 2);
+        let mut points = vec![points; self.grid.len()];
+        let mut queue: Vec<String> = FLEET.iter().map(|&(name, _)| name.to_string()).collect();
+        let mut placed: Vec<String> = vec![];
 
         println!("Player board:\n{self}");
+        println!("While placing ships you can enter 'undo', 'clear' or 'move <ship>'");
+
+        loop {
+            let command = if queue.is_empty() {
+                println!(
+                    "Press 'y' to confirm your fleet, or enter 'undo', 'clear' or 'move <ship>'"
+                );
+                let input = read_input_line();
+                if input.trim() == "y" {
+                    break;
+                }
+                parse_placement_command(&input)
+            } else {
+                let ship = queue[0].clone();
+                let shape = shape_of(&ship, self.shaped);
+                let layer = self.layer_for(&ship);
+                if layer == Layer::Deep {
+                    println!("Your {ship} goes on the deep layer");
+                }
+                let points = &mut points[layer.index()];
+                if shape.is_line() {
+                    let size = FLEET[fleet_position(&ship)].1;
+                    self.request_ship(ship, size, points)
+                } else {
+                    self.request_shaped_ship(ship, &shape, points)
+                }
+            };
 
-        for (ship, length) in possible_ships {
-            self.request_ship(ship, length, &mut points);
+            match command {
+                PlacementCommand::Coord(_)
+                | PlacementCommand::Heading(..)
+                | PlacementCommand::Anchor(..)
+                    if !queue.is_empty() =>
+                {
+                    placed.push(queue.remove(0));
+                }
+                PlacementCommand::Undo => match placed.pop() {
+                    Some(ship) => {
+                        self.remove_user_ship(&ship, &mut points);
+                        queue.insert(0, ship);
+                    }
+                    None => println!("There are no ships to undo"),
+                },
+                PlacementCommand::Clear => {
+                    for ship in placed.drain(..).rev() {
+                        self.remove_user_ship(&ship, &mut points);
+                        queue.insert(0, ship);
+                    }
+                }
+                PlacementCommand::Move(name) => {
+                    match placed.iter().position(|ship| ship.to_lowercase() == name) {
+                        Some(pos) => {
+                            let ship = placed.remove(pos);
+                            self.remove_user_ship(&ship, &mut points);
+                            queue.insert(0, ship);
+                        }
+                        None => println!("You have not placed a ship called {name}"),
+                    }
+                }
+                _ => {
+                    println!("Please enter 'y', 'undo', 'clear' or 'move <ship>'")
+                }
+            }
             println!("Player board:\n{self}");
         }
     }
 
+    ///
+    /// Arguements:
+    ///     name: &str - the name of the ship to take back
+    ///     points: &mut [Vec<(usize, usize)>] This holds all of the unused coordinates
+    ///         in the user game board, for each layer. The ship's coordinates are added
+    ///         back to its layer's.
+    /// Removes a placed ship from the board, setting its coordinates back to blank
+    /// Returns false if there is no ship with that name
+    ///
+    fn remove_user_ship(&mut self, name: &str, points: &mut [Vec<(usize, usize)>]) -> bool {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        match self.fleet.remove(name) {
+            Some(ship) => {
+                for (row, col) in ship.cells {
+                    self.update_coord_state(
+                        row as i32 + 1,
+                        columns[col],
+                        ship.layer,
+                        CoordState::Blank,
+                    );
+                    points[ship.layer.index()].push((row, col));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
     ///
     /// Asks for user imput for ship placement
     /// Arguements:
@@ -97,66 +438,195 @@ impl Grid {
     ///     size: u32 - this is the length of the ship
     ///     points: &mut Vec<(usize, usize)> This holds all of the unused coordinates
     ///         in the user game board for validating ship placement
-    /// calls get_input_coord to get valid coordinates
+    /// reads each coordinate with parse_placement_command
+    /// If the starting coordinate has a direction after it, like A1 down, the ending
+    ///     coordinate is worked out from the size and not asked for
     /// checks that ship placement is valid
     /// if it is calls place_user_ship to check for more validity, and record the ship
-    /// Loops until a valid ship is placed successfully.
-    /// no return value
+    /// Loops until a valid ship is placed successfully, or the user enters a command
+    /// Returns PlacementCommand::Coord with the starting coordinate once the ship is placed,
+    ///     or the command the user entered
     ///
-    fn request_ship(&mut self, name: String, size: u32, points: &mut Vec<(usize, usize)>) {
-        let mut valid_ship = false;
-
+    fn request_ship(
+        &mut self,
+        name: String,
+        size: u32,
+        points: &mut Vec<(usize, usize)>,
+    ) -> PlacementCommand {
         println!("Where would you like to place your {name}? It is {size} spaces long.",);
-        while !valid_ship {
-            println!("Please enter the starting coordinate");
-            let mut start_row = 0;
-            let mut start_col = 'z';
-
-            let mut valid_start = false;
-            while !valid_start {
-                ((start_row, start_col), valid_start) = get_input_coord();
-                if !valid_start {
-                    println!("Please enter location in the format: A8")
+        loop {
+            println!("Please enter the starting coordinate, optionally with a direction: A8 down");
+            let (start, heading) = loop {
+                match parse_placement_command(&read_input_line()) {
+                    PlacementCommand::Coord(coord) => break (coord, None),
+                    PlacementCommand::Heading(coord, direction) => break (coord, Some(direction)),
+                    PlacementCommand::Invalid => {
+                        println!("Please enter location in the format: A8 or A8 down")
+                    }
+                    command => return command,
+                }
+            };
+
+            if let Some(direction) = heading {
+                match direction.end_point(start, size) {
+                    Some(end) => {
+                        if self.place_user_ship(name.clone(), start, end, size, points) {
+                            return PlacementCommand::Coord(start);
+                        }
+                        println!("Invalid ship placement. Please try again.")
+                    }
+                    None => println!("Your {name} doesn't fit on the board going that way"),
                 }
+                continue;
             }
 
             println!("Please enter the ending coordinate");
-            let mut end_row = 0;
-            let mut end_col = 'z';
-            let mut valid_end = false;
-
-            while !valid_end {
-                ((end_row, end_col), valid_end) = get_input_coord();
-                if !valid_end {
-                    println!("Please enter location in the format: A8")
+            let end = loop {
+                match parse_placement_command(&read_input_line()) {
+                    PlacementCommand::Coord(coord) => break coord,
+                    PlacementCommand::Invalid => {
+                        println!("Please enter location in the format: A8")
+                    }
+                    command => return command,
                 }
+            };
+
+            if self.place_user_ship(name.clone(), start, end, size, points) {
+                return PlacementCommand::Coord(start);
             }
+            println!("Invalid ship placement. Please try again.")
+        }
+    }
 
-            valid_ship = self.place_user_ship(
-                name.clone(),
-                (start_row, start_col),
-                (end_row, end_col),
-                size,
-                points,
-            );
-            if !valid_ship {
-                println!("Invalid ship placement. Please try again.")
+    ///
+    /// Asks for user imput for placing a ship that isn't straight
+    /// Arguements:
+    ///     name: String - this is the name of the ship and how it will be saved
+    ///     shape: &Shape - the ship's shape
+    ///     points: &mut Vec<(usize, usize)> This holds all of the unused coordinates
+    ///         in the user game board for validating ship placement
+    /// Draws each way the ship can lie, numbered, and reads the top left corner of the
+    ///     box around the ship and the number of the way it lies, like A8 2
+    /// calls place_shaped_ship to check that it is valid, and record the ship
+    /// Loops until a valid ship is placed successfully, or the user enters a command
+    /// Returns PlacementCommand::Anchor once the ship is placed, or the command the
+    ///     user entered
+    ///
+    fn request_shaped_ship(
+        &mut self,
+        name: String,
+        shape: &Shape,
+        points: &mut Vec<(usize, usize)>,
+    ) -> PlacementCommand {
+        println!(
+            "Where would you like to place your {name}? It takes up {} spaces, like one of these:",
+            shape.len()
+        );
+        let drawings: Vec<Vec<String>> = shape
+            .orientations(self.topology)
+            .iter()
+            .map(Shape::draw)
+            .collect();
+        let height = drawings.iter().map(Vec::len).max().unwrap_or(0);
+        let numbers: Vec<String> = (1..=drawings.len()).map(|i| format!("{i:<6}")).collect();
+        println!("  {}", numbers.concat());
+        for row in 0..height {
+            let line: Vec<String> = drawings
+                .iter()
+                .map(|drawing| format!("{:<6}", drawing.get(row).map_or("", String::as_str)))
+                .collect();
+            println!("  {}", line.concat());
+        }
+        loop {
+            println!("Please enter the top left corner of its box and the number of the way it lies: A8 2");
+            match parse_placement_command(&read_input_line()) {
+                PlacementCommand::Anchor(anchor, orientation) => {
+                    if self.place_shaped_ship(name.clone(), shape, anchor, orientation, points) {
+                        return PlacementCommand::Anchor(anchor, orientation);
+                    }
+                    println!("Invalid ship placement. Please try again.")
+                }
+                PlacementCommand::Coord(_)
+                | PlacementCommand::Heading(..)
+                | PlacementCommand::Invalid => {
+                    println!("Please enter location in the format: A8 2")
+                }
+                command => return command,
             }
         }
     }
 
+    ///
+    /// Arguements:
+    ///     name: String - the name of the ship to be placed
+    ///     shape: &Shape - the ship's shape
+    ///     anchor: (i32, char) - the coordinate of the top left corner of the box around the ship
+    ///     orientation: usize - which of shape.orientations() it lies as, counting from 1,
+    ///         on this board
+    ///     points: &mut Vec<(usize, usize)> This holds all of the unused coordinates
+    ///         in the user game board for validating ship placement, on the ship's layer.
+    ///         If the placement is valid all of the coordinates being used will be deleted
+    ///         from points.
+    /// Returns bool - whether or not the ship placement was valid
+    /// Unless the grid is quiet, prints why a placement isn't valid
+    ///
+    pub(crate) fn place_shaped_ship(
+        &mut self,
+        name: String,
+        shape: &Shape,
+        anchor: (i32, char),
+        orientation: usize,
+        points: &mut Vec<(usize, usize)>,
+    ) -> bool {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let orientations = shape.orientations(self.topology);
+        if orientation == 0 || orientation > orientations.len() {
+            self.say(&format!(
+                "Please pick the way your {name} lies, from 1 to {}",
+                orientations.len()
+            ));
+            return false;
+        }
+        let ship = match on_board(anchor) {
+            true => orientations[orientation - 1].at(get_coord_index(anchor.0, anchor.1)),
+            false => None,
+        };
+        let Some(ship) = ship else {
+            self.say("Your ship must be on the board");
+            return false;
+        };
+        if ship.iter().any(|point| !points.contains(point)) {
+            self.say("You can not place a ship on top of another ship.");
+            return false;
+        }
+
+        let layer = self.layer_for(&name);
+        for (row, col) in &ship {
+            self.update_coord_state(*row as i32 + 1, columns[*col], layer, CoordState::Ship);
+            if let Some(pos) = points.iter().position(|x| *x == (*row, *col)) {
+                points.remove(pos);
+            }
+        }
+        self.fleet.add_on(&name, ship, layer);
+        true
+    }
+
     ///
     /// Arguements:
     ///     name: Sring - the name of the ship to be places
     ///     start_point: (i32, char) - the coordinate of one end of the ship
-    ///     end_point: (i32, char) - the coordinate of the other end of the ship
+    ///     end_point: (i32, char) - the coordinate of the other end of the ship. The ends are
+    ///         in the same row or column, or on a hex board they can be down and to the left
+    ///         of each other, like D1 and A4
     ///     given_size: u32 - the expected size of the ship, for validation
     ///     points: &mut Vec<(usize, usize)> This holds all of the unused coordinates
-    ///         in the user game board for validating ship placement. If the placement is valid
-    ///         all of the coordinates being used will be deleted from points.
+    ///         in the user game board for validating ship placement, on the ship's layer.
+    ///         If the placement is valid all of the coordinates being used will be deleted
+    ///         from points.
     /// Returns bool - whether or not the ship placement was valid
+    /// Unless the grid is quiet, prints why a placement isn't valid
     ///     
-    fn place_user_ship(
+    pub(crate) fn place_user_ship(
         &mut self,
         name: String,
         start_point: (i32, char),
@@ -166,45 +636,73 @@ impl Grid {
     ) -> bool {
         let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
         let mut valid = true;
-        let size = given_size - 1;
+        let size = given_size.saturating_sub(1);
         let (start_row, start_col) = start_point;
         let (end_row, end_col) = end_point;
 
         let mut ship: Vec<(usize, usize)> = vec![];
 
-        if start_row != end_row && start_col != end_col {
-            println!("Your ship must go in a straight line");
+        if !on_board(start_point) || !on_board(end_point) {
+            self.say("Your ship must be on the board");
+            return false;
+        }
+        let (_, start_idx) = get_coord_index(1, start_col);
+        let (_, end_idx) = get_coord_index(1, end_col);
+        // on a hex board a ship can also run down and to the left
+        let diagonal = self.topology == Topology::Hex
+            && end_row - start_row == start_idx as i32 - end_idx as i32;
+
+        if start_row != end_row && start_col != end_col && !diagonal {
+            self.say("Your ship must go in a straight line");
             valid = false;
         } else if start_row == end_row && start_col == end_col {
-            println!("Your ship must take up more than one space");
+            self.say("Your ship must take up more than one space");
             valid = false;
         }
 
         if valid {
-            if start_row != end_row {
+            if diagonal {
+                let (top_row, right_idx) = if start_row < end_row {
+                    (start_row, start_idx)
+                } else {
+                    (end_row, end_idx)
+                };
+                if (end_row - start_row).abs() != size as i32 {
+                    self.say(&format!("Your ship must be {given_size} spaces long"));
+                    valid = false
+                }
+                for i in 0..=(end_row - start_row).abs() {
+                    let point = ((top_row + i - 1) as usize, right_idx - i as usize);
+                    if !points.contains(&point) {
+                        self.say("You can not place a ship on top of another ship.");
+                        valid = false
+                    }
+                    ship.push(point);
+                }
+            } else if start_row != end_row {
                 let (_, c_start_idx) = get_coord_index(start_row, start_col);
                 if start_row < end_row {
                     if end_row - start_row != size as i32 {
-                        println!("Your ship must be {given_size} spaces long");
+                        self.say(&format!("Your ship must be {given_size} spaces long"));
                         valid = false
                     }
                     for i in start_row..=end_row {
                         let point = (i as usize - 1, c_start_idx);
                         if !points.contains(&point) {
-                            println!("You can not place a ship on top of another ship.");
+                            self.say("You can not place a ship on top of another ship.");
                             valid = false
                         }
                         ship.push(point);
                     }
                 } else {
                     if start_row - end_row != size as i32 {
-                        println!("Your ship must be {given_size} spaces long");
+                        self.say(&format!("Your ship must be {given_size} spaces long"));
                         valid = false;
                     }
                     for i in end_row..=start_row {
                         let point = (i as usize - 1, c_start_idx);
                         if !points.contains(&point) {
-                            println!("You can not place a ship on top of another ship.");
+                            self.say("You can not place a ship on top of another ship.");
                             valid = false
                         }
                         ship.push(point);
@@ -216,26 +714,26 @@ impl Grid {
 
                 if c_start_idx < c_end_idx {
                     if c_end_idx - c_start_idx != size as usize {
-                        println!("Your ship must be {given_size} spaces long");
+                        self.say(&format!("Your ship must be {given_size} spaces long"));
                         valid = false;
                     }
                     for i in c_start_idx..=c_end_idx {
                         let point = ((start_row - 1) as usize, i);
                         if !points.contains(&point) {
-                            println!("You can not place a ship on top of another ship.");
+                            self.say("You can not place a ship on top of another ship.");
                             valid = false
                         }
                         ship.push(point);
                     }
                 } else {
                     if c_start_idx - c_end_idx != size as usize {
-                        println!("Your ship must be {given_size} spaces long");
+                        self.say(&format!("Your ship must be {given_size} spaces long"));
                         valid = false
                     }
                     for i in c_end_idx..=c_start_idx {
                         let point = ((start_row - 1) as usize, i);
                         if !points.contains(&point) {
-                            println!("You can not place a ship on top of another ship.");
+                            self.say("You can not place a ship on top of another ship.");
                             valid = false
                         }
                         ship.push(point);
@@ -245,261 +743,207 @@ impl Grid {
         }
 
         if valid {
+            let layer = self.layer_for(&name);
             for (row, col) in &ship {
-                self.update_coord_state(*row as i32 + 1, columns[*col], CoordState::Ship);
+                self.update_coord_state(*row as i32 + 1, columns[*col], layer, CoordState::Ship);
                 if let Some(pos) = points.iter().position(|x| *x == (*row, *col)) {
                     points.remove(pos);
                 }
             }
-            self.ships.insert(name.clone(), ship);
+            self.fleet.add_on(&name, ship, layer);
         }
         valid
     }
 
     ///
-    /// Driver for setting computer ships
-    /// Creates a hashmap of 4 biggest ships
-    /// Creates vector of points for placement validation
-    /// creates vector of quadrants to aid in legal ship placement
-    /// randomly selects ship and quadrant, then calls create_ship() which creates a legal ship
-    /// Once the 4 biggest ships are placed, create_ship is called for the smallest ship and a random quadrant
-    /// no return value
+    /// Returns the (row, column) of every point on the layer without a ship on it
     ///
-    pub fn set_computer_ships(&mut self) {
-        let mut possible_ships: HashMap<String, u32> = HashMap::new();
-        possible_ships.insert("Carrier".to_string(), 5);
-        possible_ships.insert("Battleship".to_string(), 4);
-        possible_ships.insert("Submarine".to_string(), 3);
-        possible_ships.insert("Cruiser".to_string(), 3);
-
+    fn open_points(&self, layer: Layer) -> Vec<(usize, usize)> {
         let mut points = vec![];
         for i in 0..10 {
             for j in 0..10 {
-                points.push((i, j));
+                if self.fleet.ship_at(layer, (i, j)).is_none() {
+                    points.push((i, j));
+                }
             }
         }
+        points
+    }
 
-        let mut quadrants = vec![0, 1, 2, 3];
-
-        for ship in possible_ships {
-            let q_idx = (random::<f32>() * quadrants.len() as f32).floor() as usize;
-            let quadrant = quadrants.remove(q_idx);
-            self.create_ship(ship, &mut points, quadrant);
+    ///
+    /// Places a ship, for code using the library rather than a player
+    /// Arguements:
+    ///     name: &str - the name of the ship
+    ///     start_point: (i32, char) - the coordinate of one end of the ship
+    ///     end_point: (i32, char) - the coordinate of the other end of the ship
+    ///     size: u32 - the length of the ship
+    /// Returns an error if the ship can't go there, see place_user_ship
+    /// The ship is shown on the board, like the user's ships, on the layer from layer_for
+    ///
+    pub fn place_ship(
+        &mut self,
+        name: &str,
+        start_point: (i32, char),
+        end_point: (i32, char),
+        size: u32,
+    ) -> Result<(), String> {
+        let mut points = self.open_points(self.layer_for(name));
+        let quiet = self.quiet;
+        self.quiet = true;
+        let placed =
+            self.place_user_ship(name.to_string(), start_point, end_point, size, &mut points);
+        self.quiet = quiet;
+        match placed {
+            true => Ok(()),
+            false => Err(format!(
+                "The {name} can't go from {}{} to {}{}",
+                start_point.1, start_point.0, end_point.1, end_point.0
+            )),
         }
-
-        let quad = (random::<f32>() * 4.).floor() as usize;
-        self.create_ship(("Destroyer".to_string(), 2), &mut points, quad)
     }
 
     ///
+    /// Places a ship at random, for code using the library rather than a player
     /// Arguements:
-    ///     ship_info: (String, u32) - Name and size of the ship to be created
-    ///     points: &mut Vec<(i32, i32)> - a vector of available coordinates for legal ship placement
-    ///     quadrant: usize, between 0-3, 0 being upper left quarter, 1 is upper right, 2 is
-    ///         lower left, 3 is lower right. The starting point of the ship will be within the given (random)
-    ///         quadrant, to stop potential hanging loops as a result of not having an valid places to put the ships.
-    /// No return value
-    /// Matches on the quadrant, randomly chooses a start coordinate that is in the points vector, and tries to place a ship
-    ///     going away from the corner.
-    ///     For example, in quadrant 0, the starting location for the ship will be in row 1-5 and column A-E. Then the ending
-    ///         location will randomly either be to the right or directly below the start location.
-    /// The functions loops until a valid placement has been made.
-    /// Once a valid placement has been found, the ship is pushed onto the grid.ships HashMap
-    ///
-    fn create_ship(
-        &mut self,
-        ship_info: (String, u32),
-        points: &mut Vec<(i32, i32)>,
-        quadrant: usize,
-    ) {
-        let (name, length) = ship_info;
+    ///     name: &str - the name of the ship
+    ///     size: u32 - the length of the ship
+    /// Returns an error if there is no room left for it, see create_ship
+    /// The ship is hidden, like the computer's ships, on the layer from layer_for
+    ///
+    pub fn place_random_ship(&mut self, name: &str, size: u32) -> Result<(), String> {
+        let mut points: Vec<(i32, i32)> = self
+            .open_points(self.layer_for(name))
+            .iter()
+            .map(|&(row, col)| (row as i32, col as i32))
+            .collect();
+        self.create_ship((name.to_string(), size), &mut points)
+    }
 
-        let mut ship: Vec<(usize, usize)> = vec![];
-        let mut valid_ship = false;
-        let mut row = 0;
-        let mut col = 0;
-
-        match quadrant {
-            0 => {
-                while !valid_ship {
-                    let mut valid_start = false;
-                    while !valid_start {
-                        row = (random::<f32>() * 5.).floor() as i32;
-                        col = (random::<f32>() * 5.).floor() as i32;
-                        if points.contains(&(row, col)) {
-                            valid_start = true
-                        }
+    ///
+    /// Driver for setting computer ships
+    /// Arguements:
+    ///     style: PlacementStyle - how the ships are placed
+    ///         Random calls set_random_ships
+    ///         Adversarial makes ADVERSARIAL_LAYOUTS random layouts and keeps the one with
+    ///         the highest hunt_score, the one common hunting patterns take longest to find.
+    ///         Only the ships on the surface are scored, since that is where hunters look.
+    /// Returns an error if one of the ships has no legal placement left
+    ///
+    pub fn set_computer_ships(&mut self, style: PlacementStyle) -> Result<(), String> {
+        match style {
+            PlacementStyle::Random => self.set_random_ships(),
+            PlacementStyle::Adversarial => {
+                let mut best_score = f32::MIN;
+                for _ in 0..ADVERSARIAL_LAYOUTS {
+                    let mut candidate = Grid::new();
+                    candidate.shaped = self.shaped;
+                    candidate.topology = self.topology;
+                    if self.is_layered() {
+                        candidate.add_deep_layer();
                     }
-                    let direction = (random::<f32>() * 2.).floor() as i32;
-                    match direction {
-                        0 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row + i, col);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        1 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row, col + i);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        _ => {}
+                    candidate.set_random_ships()?;
+                    let surface = candidate.fleet.on_layer(Layer::Surface);
+                    let score = hunt_score(&surface, self.topology);
+                    if score > best_score {
+                        best_score = score;
+                        self.fleet = candidate.fleet;
                     }
                 }
+                Ok(())
             }
-            1 => {
-                while !valid_ship {
-                    let mut valid_start = false;
-                    while !valid_start {
-                        row = (random::<f32>() * 5.).floor() as i32;
-                        col = (random::<f32>() * 5.).floor() as i32 + 5;
-                        if points.contains(&(row, col)) {
-                            valid_start = true
-                        }
-                    }
-                    let direction = (random::<f32>() * 2.).floor() as i32;
-                    match direction {
-                        0 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row + i, col);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        1 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row, col - i);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        _ => {}
-                    }
-                }
+        }
+    }
+
+    ///
+    /// Places the computer ships at random, in FLEET order
+    /// Ships that dive go on the deep layer, if there is one, see layer_for
+    /// Creates vector of points for placement validation, for each layer
+    /// calls create_ship() for each ship, which picks a random legal placement
+    /// Returns an error if one of the ships has no legal placement left
+    ///
+    fn set_random_ships(&mut self) -> Result<(), String> {
+        let mut points = vec![];
+        for i in 0..10 {
+            for j in 0..10 {
+                points.push((i, j));
             }
+        }
+        let mut points = vec![points; self.grid.len()];
 
-            2 => {
-                while !valid_ship {
-                    let mut valid_start = false;
-                    while !valid_start {
-                        row = (random::<f32>() * 5.).floor() as i32 + 5;
-                        col = (random::<f32>() * 5.).floor() as i32;
-                        if points.contains(&(row, col)) {
-                            valid_start = true
-                        }
-                    }
-                    let direction = (random::<f32>() * 2.).floor() as i32;
-                    match direction {
-                        0 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row - i, col);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        1 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row, col + i);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        _ => {}
-                    }
-                }
+        for (name, length) in FLEET {
+            let layer = self.layer_for(name);
+            self.create_ship((name.to_string(), length), &mut points[layer.index()])?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Arguements:
+    ///     shape: &Shape - the shape of the ship
+    ///     points: &Vec<(i32, i32)> - a vector of available coordinates for legal ship placement
+    /// Returns every legal placement of the ship, each as the list of its (row, column) points.
+    /// A straight placement starts on any available point and runs along one of the board's
+    /// axes: down or to the right, or down and to the left on a hex board. Any other shape
+    /// can lie any way it can be turned or flipped over. A placement is legal when every
+    /// point it covers is still available.
+    ///
+    fn ship_placements(&self, shape: &Shape, points: &[(i32, i32)]) -> Vec<Vec<(usize, usize)>> {
+        if !shape.is_line() {
+            let mut free = vec![vec![false; 10]; 10];
+            for &(row, col) in points {
+                free[row as usize][col as usize] = true;
             }
+            return shape.placements(&free, self.topology);
+        }
+        let length = shape.len() as u32;
+        let mut placements = vec![];
 
-            3 => {
-                while !valid_ship {
-                    let mut valid_start = false;
-                    while !valid_start {
-                        row = (random::<f32>() * 5.).floor() as i32 + 5;
-                        col = (random::<f32>() * 5.).floor() as i32 + 5;
-                        if points.contains(&(row, col)) {
-                            valid_start = true
-                        }
-                    }
-                    let direction = (random::<f32>() * 2.).floor() as i32;
-                    match direction {
-                        0 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row - i, col);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        1 => {
-                            let mut v = true;
-                            for i in 0..length as i32 {
-                                let point = (row, col - i);
-                                if !points.contains(&point) {
-                                    v = false;
-                                    ship = vec![];
-                                    break;
-                                } else {
-                                    ship.push((point.0 as usize, point.1 as usize))
-                                }
-                            }
-                            valid_ship = v
-                        }
-                        _ => {}
-                    }
+        for &(row, col) in points {
+            for (d_row, d_col) in self.topology.axes() {
+                let ship: Vec<(i32, i32)> = (0..length as i32)
+                    .map(|i| (row + d_row * i, col + d_col * i))
+                    .collect();
+                if ship.iter().all(|point| points.contains(point)) {
+                    placements.push(
+                        ship.iter()
+                            .map(|&(r, c)| (r as usize, c as usize))
+                            .collect(),
+                    );
                 }
             }
-            _ => (),
         }
+        placements
+    }
+
+    ///
+    /// Arguements:
+    ///     ship_info: (String, u32) - Name and size of the ship to be created
+    ///     points: &mut Vec<(i32, i32)> - a vector of available coordinates for legal ship
+    ///         placement, on the ship's layer
+    /// Returns an error if there is no legal place left for the ship
+    /// The ship's shape comes from shape_of, unless that isn't the given size, when it is straight
+    /// Gets every legal placement from ship_placements and randomly chooses one of them
+    /// The chosen points are removed from points and the ship is added to self.fleet, on
+    /// the layer from layer_for
+    ///
+    pub(crate) fn create_ship(
+        &mut self,
+        ship_info: (String, u32),
+        points: &mut Vec<(i32, i32)>,
+    ) -> Result<(), String> {
+        let (name, length) = ship_info;
+
+        let shape = match shape_of(&name, self.shaped) {
+            shape if shape.len() == length as usize => shape,
+            _ => Shape::line(length),
+        };
+        let mut placements = self.ship_placements(&shape, points);
+        if placements.is_empty() {
+            return Err(format!("There is no room left to place the {name}"));
+        }
+        let index = (random::<f32>() * placements.len() as f32).floor() as usize;
+        let ship = placements.remove(index);
+
         for point in &ship {
             let p = (point.0 as i32, point.1 as i32);
 
@@ -507,60 +951,44 @@ impl Grid {
                 points.remove(pos);
             }
         }
-        self.ships.insert(name, ship);
+        let layer = self.layer_for(&name);
+        self.fleet.add_on(&name, ship, layer);
+        Ok(())
     }
 
     ///
     /// Arguments:
-    ///     possible_guesses: &mut PossibleGuesses
-    ///         This keeps track of what points are available to guessed, and has
-    ///         methods that assist with the AI guessing.
-    /// Checks status of PossibleGuesses
-    /// If possible_guesses.reasonable_guesses or possible_guesses.next_guesses aren't empty,
-    /// there has been a hit recently, without sinking a ship.
-    /// In that case non_random_guess is called, which will pull form either
-    /// of those vectors.
-    ///  
-    /// If those two are empty, it calls random_guess
+    ///     guesser: &mut dyn Guesser
+    ///         The computer opponent, which picks the guess and is told the result.
+    ///         PossibleGuesses and MonteCarloGuesser are both Guessers.
+    /// Asks the guesser for its next guess and calls computer_guess_helper with it
     ///
     /// Returns a bool reporting if the game is over. (True if it is over)
+    /// Returns the guesser's error instead if it has nothing left to guess
     ///
-    pub fn make_computer_guess(&mut self, possible_guesses: &mut PossibleGuesses) -> bool {
-        if possible_guesses.reasonable_guesses.is_empty()
-            && possible_guesses.next_guesses.is_empty()
-        {
-            let (col, row) = possible_guesses.random_guess();
-            self.computer_guess_helper(col, row, possible_guesses)
-        } else {
-            let (col, row) = possible_guesses.non_random_guess();
-            self.computer_guess_helper(col, row, possible_guesses)
-        }
+    pub fn make_computer_guess(&mut self, guesser: &mut dyn Guesser) -> Result<bool, String> {
+        let (col, row) = guesser.next_guess()?;
+        Ok(self.computer_guess_helper(col, row, guesser))
     }
 
     ///
     /// Arguements:
     ///     col: char - between A-J, the column of the guess
     ///     row: i32 - between 1-10, the row of the guess
-    ///     possible_guesses: &mut PossibleGuesses
-    ///         This keeps track of what points are available to guessed, and has
-    ///         methods that assist with the AI guessing.
+    ///     guesser: &mut dyn Guesser
+    ///         The computer opponent, which is told the result of the guess
     /// A helper function for making a guess
     /// Calls guess
     /// returns a bool, true if the game over, false otherwise
     ///
-    fn computer_guess_helper(
-        &mut self,
-        col: char,
-        row: i32,
-        possible_guesses: &mut PossibleGuesses,
-    ) -> bool {
+    fn computer_guess_helper(&mut self, col: char, row: i32, guesser: &mut dyn Guesser) -> bool {
         let mut finished = false;
-        println!("Computer guess: {col}{row}");
+        if !self.quiet {
+            println!("Computer guess: {col}{row}");
+        }
 
         let report = self.guess(row, col);
-        if report.0 == 'h' {
-            possible_guesses.update_guesses(report, col, row)
-        }
+        guesser.record_guess(report, col, row);
         if report.1 .0 == 'f' {
             finished = true;
         }
@@ -571,18 +999,33 @@ impl Grid {
     /// Promts the user to make a guess
     /// Calls get get_input_coord to get a valid coordinate from the user
     /// calls guess
+    /// Arguements:
+    ///     deadline: &mut Option<(Instant, String)> - when the turn runs out of time and
+    ///         the guess to make for the user if it does, or None for no limit
     /// Returns true if the game is over
     ///
-    pub fn request_user_guess(&mut self) -> bool {
+    pub(crate) fn request_user_guess(&mut self, deadline: &mut Option<(Instant, String)>) -> bool {
         let mut finished = false;
         let mut valid_guess = false;
 
         let mut guess_row = 0;
         let mut guess_col = 'z';
 
+        if self.is_layered() {
+            println!("Please make a guess, with 'deep' after it for the deep layer: A8 deep");
+            loop {
+                match parse_layered_coord(&read_timed_line(deadline)) {
+                    Some(((row, col), layer)) => {
+                        return self.guess_layer(row, col, layer).1 .0 == 'f'
+                    }
+                    None => println!("Please make a guess in the format: A8 or A8 deep"),
+                }
+            }
+        }
+
         println!("Please make a guess");
         while !valid_guess {
-            ((guess_row, guess_col), valid_guess) = get_input_coord();
+            ((guess_row, guess_col), valid_guess) = parse_coord(&read_timed_line(deadline));
             if !valid_guess {
                 println!("Please make a guess in the format: A8");
             }
@@ -595,87 +1038,192 @@ impl Grid {
         finished
     }
 
+    ///
+    /// Makes a guess at the surface, see guess_layer
+    ///
+    pub(crate) fn guess(&mut self, row: i32, column: char) -> (char, (char, i32)) {
+        self.guess_layer(row, column, Layer::Surface)
+    }
+
+    ///
+    /// Returns true if a point in the given state can be guessed: it hasn't been hit,
+    /// isn't a detonated mine, and isn't a miss unless ships can move onto it
+    ///
+    pub(crate) fn can_guess(&self, state: CoordState) -> bool {
+        !(state == CoordState::Hit
+            || state == CoordState::Detonated
+            || (state == CoordState::Miss && !self.mobile))
+    }
+
     ///
     /// Makes a guess
     /// Arguments:
     ///     row: i32 - 1-10 - the row the guess is on
     ///     col: char - A-J - the column the guess is on
+    ///     layer: Layer - the layer of the board it is aimed at
     /// Returns (h: char, (s: char, l:i32))
-    ///     h: hit or miss
-    ///     s: n for not hit, u for not sunk, s for sunk, f for finished
+    ///     h: hit or miss, or x for a mine
+    ///     s: n for not hit, u for not sunk, s for sunk, f for finished,
+    ///         a for a hit that was already known, still there after a repair
     ///     l: length of hit ship (for the computer smart guesser)
     ///
     /// First checks to see that the coordinate hasn't already been guess
+    ///     (a miss can be guessed again if the grid is mobile)
+    /// An uncertain hit, after a repair, that is still hit goes back to being a hit and is
+    ///     reported as h with a. One that was repaired is guessed as usual.
+    /// Then looks for a mine or decoy there, which are only on the surface
+    ///     A mine is detonated and reported as x
+    ///     A decoy is reported as a hit on a ship that isn't sunk, with no ship
     /// Then looks to see if there is a ship there
     ///     If there is not, returns a miss
-    ///     If there is, calls check_ship_status
-    ///         and changes that coordinate in the shap to (100, 100) to show it was hit
+    ///     If there is, marks that point of the ship as damaged and calls check_ship_status
+    /// Every guess, even a repeated one, is added to self.history
     ///
-    fn guess(&mut self, row: i32, column: char) -> (char, (char, i32)) {
+    pub(crate) fn guess_layer(
+        &mut self,
+        row: i32,
+        column: char,
+        layer: Layer,
+    ) -> (char, (char, i32)) {
         // check if status of coordinate is not blank
         let rc = get_coord_index(row, column);
+        let state = self.grid[layer.index()][rc.0][rc.1].state;
 
-        let mut found = false;
-        let mut found_ship = "".to_string();
-        let mut idx: usize = 0;
+        let mut found = None;
         let mut g = 'm'; // for miss
         let mut s = ('n', 0); // for not sunk
 
-        if self.grid[rc.0][rc.1].state == CoordState::Hit
-            || self.grid[rc.0][rc.1].state == CoordState::Miss
-        {
-            println!("Already guessed: {column}{row}");
+        if !self.can_guess(state) {
+            if !self.quiet {
+                match layer {
+                    Layer::Surface => println!("Already guessed: {column}{row}"),
+                    Layer::Deep => println!("Already guessed: {column}{row} deep"),
+                }
+            }
+            self.history.push(Shot {
+                row,
+                column,
+                report: (g, s),
+                ship: None,
+                layer,
+            });
             return (g, s);
         }
 
-        for (ship, coords) in &mut self.ships {
-            for (i, coord) in coords.iter().enumerate() {
-                if rc == *coord {
-                    found = true;
-                    found_ship = ship.to_string();
-                    idx = i;
-                    break;
-                }
+        let still_hit = self.decoys.contains(&rc)
+            || self
+                .fleet
+                .ship_at(layer, rc)
+                .is_some_and(|ship| ship.is_hit(rc));
+        if state == CoordState::Uncertain && still_hit {
+            self.update_coord_state(row, column, layer, CoordState::Hit);
+            if !self.quiet {
+                println!("Still hit: {column}{row}");
             }
+            let report = ('h', ('a', 0));
+            self.history.push(Shot {
+                row,
+                column,
+                report,
+                ship: self.fleet.ship_at(layer, rc).map(|ship| ship.id),
+                layer,
+            });
+            return report;
         }
 
-        if !found {
-            self.update_coord_state(row, column, CoordState::Miss);
-            println!("Miss");
-        } else {
-            let mut new_coords = self.ships.get(&found_ship).unwrap().clone();
-            new_coords[idx] = (100, 100);
-            self.ships.insert(found_ship.clone(), new_coords);
-            self.update_coord_state(row, column, CoordState::Hit);
-            println!("Hit!");
+        let trap = layer == Layer::Surface;
+        if trap && (self.mines.contains(&rc) || self.decoys.contains(&rc)) {
+            let mine = self.mines.contains(&rc);
+            let report = if mine {
+                self.update_coord_state(row, column, layer, CoordState::Detonated);
+                ('x', ('n', 0))
+            } else {
+                self.update_coord_state(row, column, layer, CoordState::Hit);
+                ('h', ('u', 0))
+            };
+            if !self.quiet {
+                println!("{}", if mine { "Mine!" } else { "Hit!" });
+            }
+            self.history.push(Shot {
+                row,
+                column,
+                report,
+                ship: None,
+                layer,
+            });
+            return report;
+        }
+
+        if let Some(ship) = self.fleet.ship_at_mut(layer, rc) {
+            ship.hit(rc);
+            found = Some(ship.id);
+        }
+
+        if let Some(id) = found {
+            self.update_coord_state(row, column, layer, CoordState::Hit);
+            if !self.quiet {
+                println!("Hit!");
+            }
             g = 'h';
-            s = self.check_ship_status(found_ship);
+            s = self.check_ship_status(id);
+        } else {
+            self.update_coord_state(row, column, layer, CoordState::Miss);
+            if !self.quiet {
+                println!("Miss");
+            }
         }
         let report: (char, (char, i32)) = (g, s);
+        self.history.push(Shot {
+            row,
+            column,
+            report,
+            ship: found,
+            layer,
+        });
         report
     }
 
+    ///
+    /// Fires at a point on the board, for code using the library rather than a player
+    /// Arguements:
+    ///     row: i32 - 1-10 - the row to fire at
+    ///     column: char - A-J - the column to fire at
+    ///     layer: Layer - the layer to fire at, Layer::Surface without the layers rule
+    /// Returns what the shot did, or an error if the point is not on the board or the
+    /// board has no such layer
+    /// The shot is recorded in self.history, the same as with guess
+    ///
+    pub fn fire(&mut self, row: i32, column: char, layer: Layer) -> Result<Outcome, String> {
+        if !on_board((row, column)) {
+            return Err(format!("{column}{row} is not on the board"));
+        }
+        if layer.index() >= self.grid.len() {
+            return Err(format!("This board has no {layer} layer"));
+        }
+        Ok(Outcome::from_report(self.guess_layer(row, column, layer)))
+    }
+
     ///
     /// Arguments:
-    ///     hit_ship: String - the name of the ship that was just hit
+    ///     hit_ship: ShipId - the id of the ship that was just hit
     /// Returns (s: char, l: i32)
     ///     s: s for sunk, f for finished
     ///     l: 0 if not sunk, or the length of the ship that was just sunk
     /// checks to see if the ship that was just hit is now sunk
-    ///     (by seeing if all of the coordinates for that ship == (100, 100))
-    /// if it is, it removes the ship from grid.ships and calls check_endgame
+    ///     (by seeing if every point of that ship is damaged)
+    /// if it is, it calls check_endgame
     ///
-    fn check_ship_status(&mut self, hit_ship: String) -> (char, i32) {
-        let coords = self.ships.get(&hit_ship).unwrap();
+    fn check_ship_status(&mut self, hit_ship: ShipId) -> (char, i32) {
+        let ship = self.fleet.ship(hit_ship).unwrap();
         let mut s = 'u';
         let mut l = 0;
 
-        let sunk = coords.iter().all(|&coord| coord == (100, 100));
-        if sunk {
+        if ship.is_sunk() {
             s = 's'; // for sunk
-            l = coords.len();
-            println!("{hit_ship} sunk!");
-            self.ships.remove(&hit_ship);
+            l = ship.len();
+            if !self.quiet {
+                println!("{} sunk!", ship.class);
+            }
             let f = self.check_endgame();
             if f {
                 s = 'f' // for finished
@@ -685,42 +1233,122 @@ impl Grid {
     }
 
     ///
-    /// As ships are sunk, they are deleted from the grid.ships
-    /// When grid.ships is empty, this function returns true,
+    /// When every ship in the fleet has been sunk, on every layer,
+    /// this function returns true,
     /// prints a game over message and
     /// triggers endgame.
     ///
     fn check_endgame(&mut self) -> bool {
         let mut finished = false;
-        if self.ships.is_empty() {
-            println!("\t\tGAME OVER\n\n\n");
+        if self.fleet.all_sunk() {
+            if !self.quiet {
+                println!("\t\tGAME OVER\n\n\n");
+            }
             finished = true;
         }
         finished
     }
 }
 
+impl Grid {
+    ///
+    /// Returns the board as lines of text, with every placed ship shown, even the
+    /// ones the other player hasn't found.
+    /// Ship points that have not been hit are drawn as the theme's hidden ship, ones that
+    /// have as hits, even if they were left uncertain by a repair, and every other point as
+    /// its state. Each point takes one space, so two boards fit side by side.
+    /// The first line is the column letters.
+    /// With the layers rule each layer is drawn this way, one under the other, after a
+    /// line with its name.
+    ///
+    pub fn reveal_rows(&self) -> Vec<String> {
+        let header = "   A  B  C  D  E  F  G  H  I  J ";
+        let mut rows = vec![];
+        for layer in self.layers() {
+            if self.is_layered() {
+                rows.push(format!("{:<width$}", layer, width = header.len()));
+            }
+            rows.push(header.to_string());
+            for (r, row) in self.grid[layer.index()].iter().enumerate() {
+                let mut line = format!("{:>2} ", r + 1);
+                for (c, space) in row.iter().enumerate() {
+                    match self.fleet.ship_at(layer, (r, c)) {
+                        Some(ship) if !ship.is_hit((r, c)) => {
+                            line.push_str(&format!("{} ", theme::current().hidden_ship()))
+                        }
+                        Some(_) => {
+                            line.push_str(&format!("{} ", theme::current().symbol(CoordState::Hit)))
+                        }
+                        None => line.push_str(&format!("{space} ")),
+                    }
+                }
+                rows.push(line);
+            }
+        }
+        rows
+    }
+}
+
+impl Grid {
+    ///
+    /// Returns a line for each ship that is still afloat, in FLEET order,
+    /// saying how many of its points were hit, and which layer it is on with the
+    /// layers rule
+    ///
+    pub fn afloat_report(&self) -> Vec<String> {
+        let mut afloat = self.fleet.afloat();
+        afloat.sort_by_key(|ship| fleet_position(&ship.class));
+        afloat
+            .iter()
+            .map(|ship| match self.is_layered() {
+                true => format!(
+                    "{} ({}): {} of {} hit",
+                    ship.class,
+                    ship.layer,
+                    ship.hits(),
+                    ship.len()
+                ),
+                false => format!("{}: {} of {} hit", ship.class, ship.hits(), ship.len()),
+            })
+            .collect()
+    }
+}
+
 impl Default for Grid {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Display for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "   A    B    C    D    E    F    G    H    I    J").unwrap();
-        let mut line_num = 1;
-        for row in &self.grid {
-            if line_num == 10 {
-                write!(f, "{line_num} ").unwrap();
+impl Grid {
+    ///
+    /// Returns one layer of the board as lines of text, the way Display draws it: as
+    /// hexagons on a hex board, otherwise as squares with the column letters on top
+    ///
+    pub(crate) fn layer_rows(&self, layer: Layer) -> Vec<String> {
+        if self.topology == Topology::Hex {
+            return self.hex_rows(layer);
+        }
+        let mut rows = vec!["   A    B    C    D    E    F    G    H    I    J".to_string()];
+        for (line_num, row) in (1..).zip(self.grid[layer.index()].iter()) {
+            let mut line = if line_num == 10 {
+                format!("{line_num} ")
             } else {
-                write!(f, "{line_num}  ").unwrap();
-            }
+                format!("{line_num}  ")
+            };
             for space in row {
-                write!(f, "{space}   ").unwrap();
+                line.push_str(&format!("{space}   "));
             }
-            writeln!(f).unwrap();
-            line_num += 1;
+            rows.push(line);
+        }
+        rows
+    }
+}
+
+impl Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.layered_rows() {
+            writeln!(f, "{line}").unwrap();
         }
         writeln!(f, "\n")
     }
@@ -729,18 +1357,19 @@ impl Display for Grid {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ship::Orientation;
 
     #[test]
     fn test_grid() {
         let mut grid1 = Grid::new();
-        assert_eq!(grid1.grid[0][0].state, CoordState::Blank);
-        assert!(grid1.ships.is_empty());
+        assert_eq!(grid1.grid[0][0][0].state, CoordState::Blank);
+        assert!(grid1.fleet.is_empty());
 
-        grid1.update_coord_state(1, 'A', CoordState::Hit);
-        assert_eq!(grid1.grid[0][0].state, CoordState::Hit);
+        grid1.update_coord_state(1, 'A', Layer::Surface, CoordState::Hit);
+        assert_eq!(grid1.grid[0][0][0].state, CoordState::Hit);
 
-        grid1.update_coord_state(10, 'J', CoordState::Miss);
-        assert_eq!(grid1.grid[9][9].state, CoordState::Miss);
+        grid1.update_coord_state(10, 'J', Layer::Surface, CoordState::Miss);
+        assert_eq!(grid1.grid[0][9][9].state, CoordState::Miss);
     }
 
     #[test]
@@ -755,17 +1384,122 @@ mod tests {
         let v1 =
             grid1.place_user_ship("Battleship".to_string(), (2, 'B'), (4, 'B'), 3, &mut points);
         assert!(v1);
-        assert_eq!(grid1.grid[1][1].state, CoordState::Ship);
-        assert_eq!(grid1.grid[2][1].state, CoordState::Ship);
-        assert_eq!(grid1.grid[3][1].state, CoordState::Ship);
+        assert_eq!(grid1.grid[0][1][1].state, CoordState::Ship);
+        assert_eq!(grid1.grid[0][2][1].state, CoordState::Ship);
+        assert_eq!(grid1.grid[0][3][1].state, CoordState::Ship);
         assert_eq!(
-            grid1.ships[&"Battleship".to_string()],
+            grid1.fleet.get("Battleship").unwrap().cells,
             vec![(1, 1), (2, 1), (3, 1)]
         );
 
         let v2 = grid1.place_user_ship("Destroyer".to_string(), (2, 'A'), (2, 'C'), 3, &mut points);
         assert!(!v2);
-        assert_eq!(grid1.grid[1][0].state, CoordState::Blank);
+        assert_eq!(grid1.grid[0][1][0].state, CoordState::Blank);
+    }
+
+    #[test]
+    fn test_user_ship_hex() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        let mut points: Vec<(usize, usize)> =
+            (0..10).flat_map(|r| (0..10).map(move |c| (r, c))).collect();
+
+        // ships only run down and to the left on a hex board
+        assert!(!grid.place_user_ship("Cruiser".to_string(), (5, 'J'), (7, 'H'), 3, &mut points));
+        grid.topology = Topology::Hex;
+        assert!(grid.place_user_ship("Cruiser".to_string(), (7, 'H'), (5, 'J'), 3, &mut points));
+        assert_eq!(
+            grid.fleet.get("Cruiser").unwrap().cells,
+            vec![(4, 9), (5, 8), (6, 7)]
+        );
+        assert!(!grid.place_user_ship("Sub".to_string(), (8, 'A'), (7, 'C'), 3, &mut points));
+    }
+
+    #[test]
+    fn test_user_ship_shaped() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        let mut points: Vec<(usize, usize)> =
+            (0..10).flat_map(|r| (0..10).map(move |c| (r, c))).collect();
+        grid.place_user_ship("Battleship".to_string(), (2, 'B'), (4, 'B'), 3, &mut points);
+
+        // a T lying upside down, its stem at the top, which can't go on the battleship
+        let t = Shape::new(&[(0, 0), (0, 1), (0, 2), (1, 1)]);
+        assert!(!grid.place_shaped_ship("T".to_string(), &t, (1, 'A'), 3, &mut points));
+        assert!(!grid.place_shaped_ship("T".to_string(), &t, (1, 'D'), 5, &mut points));
+        assert!(!grid.place_shaped_ship("T".to_string(), &t, (10, 'D'), 1, &mut points));
+        assert!(grid.place_shaped_ship("T".to_string(), &t, (1, 'D'), 3, &mut points));
+        assert_eq!(
+            grid.fleet.get("T").unwrap().cells,
+            vec![(0, 4), (1, 3), (1, 4), (1, 5)]
+        );
+        assert_eq!(grid.grid[0][1][3].state, CoordState::Ship);
+        assert!(!grid.fleet.get("T").unwrap().is_straight());
+    }
+
+    #[test]
+    fn test_placement_commands() {
+        assert_eq!(parse_placement_command("Undo\n"), PlacementCommand::Undo);
+        assert_eq!(parse_placement_command(" clear"), PlacementCommand::Clear);
+        assert_eq!(
+            parse_placement_command("move Carrier"),
+            PlacementCommand::Move("carrier".to_string())
+        );
+        assert_eq!(
+            parse_placement_command("b7"),
+            PlacementCommand::Coord((7, 'B'))
+        );
+        assert_eq!(parse_placement_command("B77"), PlacementCommand::Invalid);
+        assert_eq!(
+            parse_placement_command(" A 8\n"),
+            PlacementCommand::Coord((8, 'A'))
+        );
+        assert_eq!(
+            parse_placement_command("K1 down"),
+            PlacementCommand::Invalid
+        );
+        assert_eq!(
+            parse_placement_command("d4 2"),
+            PlacementCommand::Anchor((4, 'D'), 2)
+        );
+        assert_eq!(
+            parse_placement_command("a1 H"),
+            PlacementCommand::Heading((1, 'A'), Direction::Right)
+        );
+        assert_eq!(
+            parse_placement_command("C3 down\n"),
+            PlacementCommand::Heading((3, 'C'), Direction::Down)
+        );
+        assert_eq!(
+            parse_placement_command("C3 sideways"),
+            PlacementCommand::Invalid
+        );
+
+        assert_eq!(Direction::Right.end_point((1, 'A'), 5), Some((1, 'E')));
+        assert_eq!(Direction::Down.end_point((3, 'C'), 3), Some((5, 'C')));
+        assert_eq!(Direction::Up.end_point((3, 'C'), 3), Some((1, 'C')));
+        assert_eq!(Direction::Left.end_point((3, 'B'), 3), None);
+        assert_eq!(Direction::Down.end_point((9, 'J'), 2), Some((10, 'J')));
+
+        let mut grid1 = Grid::new();
+        let mut points = vec![];
+        for i in 0..10 {
+            for j in 0..10 {
+                points.push((i as usize, j as usize));
+            }
+        }
+        grid1.place_user_ship("Cruiser".to_string(), (2, 'B'), (4, 'B'), 3, &mut points);
+        let mut points = vec![points];
+        assert_eq!(points[0].len(), 97);
+        assert!(grid1.remove_user_ship("Cruiser", &mut points));
+        assert_eq!(points[0].len(), 100);
+        assert!(grid1.fleet.is_empty());
+        assert_eq!(grid1.grid[0][2][1].state, CoordState::Blank);
+        assert!(!grid1.remove_user_ship("Cruiser", &mut points));
+
+        let v1 =
+            grid1.place_user_ship("Carrier".to_string(), (2, 'A'), (2, 'E'), 5, &mut points[0]);
+        assert!(v1);
     }
 
     #[test]
@@ -778,13 +1512,74 @@ mod tests {
             }
         }
         let len1 = points.len();
-        grid2.create_ship(("Battleship".to_string(), 3), &mut points, 0);
+        let created = grid2.create_ship(("Battleship".to_string(), 3), &mut points);
+        assert!(created.is_ok());
         assert_eq!(len1 - 3, points.len());
-        assert!(grid2.ships.contains_key(&"Battleship".to_string()));
-        let ship = grid2.ships[&"Battleship".to_string()].clone();
+        assert!(grid2.fleet.get("Battleship").is_some());
+        let ship = grid2.fleet.get("Battleship").unwrap().cells.clone();
         for (row, col) in ship {
-            assert_eq!(grid2.grid[row][col].state, CoordState::Blank);
+            assert_eq!(grid2.grid[0][row][col].state, CoordState::Blank);
+        }
+    }
+
+    #[test]
+    fn test_fleet() {
+        assert_eq!(fleet_position("Carrier"), 0);
+        assert_eq!(fleet_position("Destroyer"), 4);
+        assert_eq!(fleet_position("Rowboat"), FLEET.len());
+
+        let mut grid1 = Grid::new();
+        grid1.set_computer_ships(PlacementStyle::Random).unwrap();
+        for (name, length) in FLEET {
+            assert_eq!(grid1.fleet.get(name).unwrap().len(), length as usize);
         }
+        let names: Vec<String> = grid1
+            .afloat_report()
+            .iter()
+            .map(|line| line.split(':').next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Carrier", "Battleship", "Cruiser", "Submarine", "Destroyer"]
+        );
+    }
+
+    #[test]
+    fn test_computer_ship_no_room() {
+        let mut grid2 = Grid::new();
+        // a single row of two points only fits a ship of length 2
+        let mut points = vec![(4, 4), (4, 5)];
+        let created = grid2.create_ship(("Cruiser".to_string(), 3), &mut points);
+        assert!(created.is_err());
+        assert_eq!(points.len(), 2);
+
+        let created = grid2.create_ship(("Destroyer".to_string(), 2), &mut points);
+        assert!(created.is_ok());
+        assert!(points.is_empty());
+        assert_eq!(
+            grid2.fleet.get("Destroyer").unwrap().cells,
+            vec![(4, 4), (4, 5)]
+        );
+
+        let mut grid3 = Grid::new();
+        assert!(grid3.set_computer_ships(PlacementStyle::Random).is_ok());
+        assert_eq!(grid3.fleet.len(), 5);
+
+        let mut grid4 = Grid::new();
+        assert!(grid4
+            .set_computer_ships(PlacementStyle::Adversarial)
+            .is_ok());
+        assert_eq!(grid4.fleet.len(), 5);
+        let mut all: Vec<(usize, usize)> = grid4
+            .fleet
+            .ships
+            .iter()
+            .flat_map(|ship| ship.cells.clone())
+            .collect();
+        assert_eq!(all.len(), 17);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 17);
     }
 
     #[test]
@@ -800,17 +1595,17 @@ mod tests {
         let mut pg = PossibleGuesses::new();
 
         let f1 = grid1.computer_guess_helper('E', 6, &mut pg);
-        assert_eq!(grid1.grid[1][0].state, CoordState::Blank);
-        assert_eq!(grid1.grid[5][4].state, CoordState::Miss);
+        assert_eq!(grid1.grid[0][1][0].state, CoordState::Blank);
+        assert_eq!(grid1.grid[0][5][4].state, CoordState::Miss);
         assert!(!f1);
 
         let f2 = grid1.computer_guess_helper('B', 3, &mut pg);
-        assert_eq!(grid1.grid[2][1].state, CoordState::Hit);
+        assert_eq!(grid1.grid[0][2][1].state, CoordState::Hit);
         assert!(!f2);
         grid1.computer_guess_helper('B', 2, &mut pg);
-        assert_eq!(grid1.grid[1][1].state, CoordState::Hit);
+        assert_eq!(grid1.grid[0][1][1].state, CoordState::Hit);
         let f3 = grid1.computer_guess_helper('B', 4, &mut pg);
-        assert_eq!(grid1.grid[3][1].state, CoordState::Hit);
+        assert_eq!(grid1.grid[0][3][1].state, CoordState::Hit);
         assert!(f3);
     }
 
@@ -829,19 +1624,19 @@ mod tests {
         grid1.place_user_ship("Destroyer".to_string(), (5, 'C'), (5, 'D'), 2, &mut points);
         grid1.place_user_ship("Battleship".to_string(), (2, 'B'), (4, 'B'), 3, &mut points);
         let (h, (s, l)) = grid1.guess(6, 'C');
-        assert_eq!(grid1.grid[5][2].state, CoordState::Miss);
+        assert_eq!(grid1.grid[0][5][2].state, CoordState::Miss);
         assert_eq!(h, 'm');
         assert_eq!(s, 'n');
         assert_eq!(l, 0);
 
         let (h, (s, l)) = grid1.guess(5, 'C');
-        assert_eq!(grid1.grid[4][2].state, CoordState::Hit);
+        assert_eq!(grid1.grid[0][4][2].state, CoordState::Hit);
         assert_eq!(h, 'h');
         assert_eq!(s, 'u');
         assert_eq!(l, 0);
 
         let (h, (s, l)) = grid1.guess(5, 'D');
-        assert_eq!(grid1.grid[4][3].state, CoordState::Hit);
+        assert_eq!(grid1.grid[0][4][3].state, CoordState::Hit);
         assert_eq!(h, 'h');
         assert_eq!(s, 's');
         assert_eq!(l, 2);
@@ -852,5 +1647,55 @@ mod tests {
         assert_eq!(h, 'h');
         assert_eq!(s, 'f');
         assert_eq!(l, 3);
+
+        assert_eq!(grid1.history.len(), 6);
+        assert!(grid1.fleet.all_sunk());
+        assert_eq!(grid1.fleet.len(), 2);
+        let battleship = grid1.fleet.get("Battleship").unwrap();
+        assert_eq!(battleship.cells, vec![(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(battleship.damage, vec![true, true, true]);
+        assert_eq!(battleship.orientation, Orientation::Vertical);
+        let rows = grid1.reveal_rows();
+        assert_eq!(rows.len(), 11);
+        assert!(rows[5].starts_with(" 5 🟦 🟦 🟥 🟥 🟦"));
+        assert!(rows[6].starts_with(" 6 🟦 🟦 🟩"));
+        assert_eq!(
+            grid1.history[2],
+            Shot {
+                row: 5,
+                column: 'D',
+                report: ('h', ('s', 2)),
+                ship: Some(grid1.fleet.get("Destroyer").unwrap().id),
+                layer: Layer::Surface,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reveal() {
+        let mut grid1 = Grid::new();
+        grid1.quiet = true;
+        let mut points = vec![(4, 4), (4, 5), (4, 6), (7, 7), (7, 8)];
+        grid1
+            .create_ship(("Cruiser".to_string(), 3), &mut points)
+            .unwrap();
+        grid1
+            .create_ship(("Destroyer".to_string(), 2), &mut points)
+            .unwrap();
+        grid1.guess(5, 'E');
+        grid1.guess(8, 'H');
+        grid1.guess(8, 'I');
+        grid1.guess(1, 'A');
+
+        let rows = grid1.reveal_rows();
+        assert_eq!(rows.len(), 11);
+        assert!(rows[1].starts_with(" 1 🟩"));
+        assert!(rows[5].starts_with(" 5 🟦 🟦 🟦 🟦 🟥 ⬜ ⬜ 🟦"));
+        assert!(rows[8].contains("🟥 🟥"));
+        assert!(!rows[8].contains(HIDDEN_SHIP));
+        assert_eq!(
+            grid1.afloat_report(),
+            vec!["Cruiser: 1 of 3 hit".to_string()]
+        );
     }
 }
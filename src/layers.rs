@@ -0,0 +1,268 @@
+use crate::coord::*;
+use crate::grid::Grid;
+use crate::possible_guess::Guesser;
+use crate::rng::random;
+use crate::ship::Fleet;
+use std::fmt::{self, Display};
+
+///
+/// The classes of ship that go on the deep layer, with the layers rule
+///
+pub const DEEP_CLASSES: [&str; 1] = ["Submarine"];
+
+///
+/// One layer of the board, with the layers rule
+///     Surface: the usual board, where every ship but the submarine sails
+///     Deep: the board under it, where the submarine dives
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Layer {
+    #[default]
+    Surface,
+    Deep,
+}
+
+impl Layer {
+    ///
+    /// Arguements:
+    ///     input: &str - a layer the user typed, not case sensitive
+    ///         surface or s for Surface, deep or d for Deep
+    /// Returns the Layer, or None if it isn't one
+    ///
+    pub fn from_input(input: &str) -> Option<Layer> {
+        match input.trim().to_lowercase().as_str() {
+            "surface" | "s" => Some(Layer::Surface),
+            "deep" | "d" => Some(Layer::Deep),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns where the layer is in Grid::grid: 0 for the surface, 1 for the deep layer
+    ///
+    pub fn index(&self) -> usize {
+        match self {
+            Layer::Surface => 0,
+            Layer::Deep => 1,
+        }
+    }
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Surface => f.pad("Surface"),
+            Layer::Deep => f.pad("Deep"),
+        }
+    }
+}
+
+///
+/// Returns the layer the ship of the given class goes on, with the layers rule
+///
+pub fn layer_of(class: &str) -> Layer {
+    if DEEP_CLASSES.contains(&class) {
+        Layer::Deep
+    } else {
+        Layer::Surface
+    }
+}
+
+///
+/// Arguements:
+///     input: &str - a coordinate the user typed, optionally followed by a layer, like A5 deep
+/// Returns the (row, column) and the layer, which is the surface if none was given, or None
+/// if the input isn't valid
+///
+pub fn parse_layered_coord(input: &str) -> Option<((i32, char), Layer)> {
+    let input = input.trim();
+    let (coord, layer) = match input.split_once(' ') {
+        Some((coord, layer)) => (coord, Layer::from_input(layer)?),
+        None => (input, Layer::Surface),
+    };
+    match parse_coord(coord) {
+        (coord, true) => Some((coord, layer)),
+        (_, false) => None,
+    }
+}
+
+///
+/// Returns how many columns a line of a board takes up in the terminal. Every symbol that
+/// isn't plain ASCII is an emoji, two columns wide.
+///
+fn width(line: &str) -> usize {
+    line.chars().count() + line.chars().filter(|c| !c.is_ascii()).count()
+}
+
+impl Grid {
+    ///
+    /// Adds an empty deep layer under the board, for the layers rule. Ships placed after
+    /// it that dive go on it, see layer_of
+    ///
+    pub fn add_deep_layer(&mut self) {
+        if !self.is_layered() {
+            let blank = Grid::new().grid.remove(0);
+            self.grid.push(blank);
+        }
+    }
+
+    ///
+    /// Returns true if the board has a deep layer
+    ///
+    pub(crate) fn is_layered(&self) -> bool {
+        self.grid.len() > 1
+    }
+
+    ///
+    /// Returns every layer the board has, the surface first
+    ///
+    pub(crate) fn layers(&self) -> Vec<Layer> {
+        [Layer::Surface, Layer::Deep]
+            .into_iter()
+            .take(self.grid.len())
+            .collect()
+    }
+
+    ///
+    /// Returns the layer the ship of the given class goes on: the one from layer_of if
+    /// the board has a deep layer, otherwise the surface
+    ///
+    pub(crate) fn layer_for(&self, class: &str) -> Layer {
+        match self.is_layered() {
+            true => layer_of(class),
+            false => Layer::Surface,
+        }
+    }
+
+    ///
+    /// Returns the surface and the deep layer drawn side by side, with their names
+    /// above them. Without a deep layer this is just the surface.
+    ///
+    pub(crate) fn layered_rows(&self) -> Vec<String> {
+        let surface = self.layer_rows(Layer::Surface);
+        if !self.is_layered() {
+            return surface;
+        }
+        let deep = self.layer_rows(Layer::Deep);
+        let across = surface.iter().map(|line| width(line)).max().unwrap_or(0) + 4;
+
+        let mut rows = vec![format!("{:<across$}{}", Layer::Surface, Layer::Deep)];
+        for (left, right) in surface.iter().zip(deep.iter()) {
+            rows.push(format!("{left}{}{right}", " ".repeat(across - width(left))));
+        }
+        rows
+    }
+
+    ///
+    /// Picks which layer the computer fires at next, from what it has seen of this board:
+    /// a layer with a ship that has been hit but not sunk first, the surface first if both
+    /// have one. Otherwise a layer is picked at random, weighted by how many points of the
+    /// ships afloat on it there are for each point that hasn't been guessed.
+    ///
+    pub(crate) fn pick_layer(&self) -> Layer {
+        if !self.is_layered() {
+            return Layer::Surface;
+        }
+        let surface = self.fleet.on_layer(Layer::Surface);
+        let deep = self.fleet.on_layer(Layer::Deep);
+        if deep.all_sunk() {
+            return Layer::Surface;
+        }
+        if surface.all_sunk() {
+            return Layer::Deep;
+        }
+        let wounded = |fleet: &Fleet| {
+            fleet
+                .ships
+                .iter()
+                .any(|ship| ship.hits() > 0 && !ship.is_sunk())
+        };
+        if wounded(&surface) {
+            return Layer::Surface;
+        }
+        if wounded(&deep) {
+            return Layer::Deep;
+        }
+
+        let share = |fleet: &Fleet, layer: Layer| {
+            let afloat: usize = fleet
+                .ships
+                .iter()
+                .filter(|ship| !ship.is_sunk())
+                .map(|ship| ship.len())
+                .sum();
+            let open = self.grid[layer.index()]
+                .iter()
+                .flatten()
+                .filter(|point| point.state == CoordState::Blank)
+                .count();
+            afloat as f32 / open.max(1) as f32
+        };
+        let deep_share = share(&deep, Layer::Deep);
+        if random::<f32>() * (share(&surface, Layer::Surface) + deep_share) < deep_share {
+            Layer::Deep
+        } else {
+            Layer::Surface
+        }
+    }
+
+    ///
+    /// The computer's guess at the deep layer
+    /// Arguements:
+    ///     guesser: &mut dyn Guesser - the computer opponent for the deep layer
+    /// Returns true if the game is over, or the guesser's error if it has nothing left to
+    /// guess
+    ///
+    pub(crate) fn make_deep_guess(&mut self, guesser: &mut dyn Guesser) -> Result<bool, String> {
+        let (col, row) = guesser.next_guess()?;
+        if !self.quiet {
+            println!("Computer guess: {col}{row} deep");
+        }
+        let report = self.guess_layer(row, col, Layer::Deep);
+        guesser.record_guess(report, col, row);
+        Ok(report.1 .0 == 'f')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        assert_eq!(
+            parse_layered_coord("a5 Deep"),
+            Some(((5, 'A'), Layer::Deep))
+        );
+        assert_eq!(
+            parse_layered_coord("J10"),
+            Some(((10, 'J'), Layer::Surface))
+        );
+        assert_eq!(parse_layered_coord("A5 sky"), None);
+        assert_eq!(layer_of("Submarine"), Layer::Deep);
+
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.add_deep_layer();
+        grid.set_computer_ships(crate::placement::PlacementStyle::Random)
+            .unwrap();
+        assert_eq!(grid.grid.len(), 2);
+        assert_eq!(grid.fleet.get("Submarine").unwrap().layer, Layer::Deep);
+        assert_eq!(grid.fleet.on_layer(Layer::Surface).len(), 4);
+        assert_eq!(grid.layered_rows().len(), 12);
+
+        // sinking the submarine doesn't finish the game while the surface has ships
+        let cells = grid.fleet.get("Submarine").unwrap().cells.clone();
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let mut report = ('m', ('n', 0));
+        for (r, c) in cells {
+            report = grid.guess_layer(r as i32 + 1, columns[c], Layer::Deep);
+        }
+        assert_eq!(report, ('h', ('s', 3)));
+        assert_eq!(grid.history.len(), 3);
+        assert!(grid.history.iter().all(|shot| shot.layer == Layer::Deep));
+        assert_eq!(grid.pick_layer(), Layer::Surface);
+        assert!(grid.fire(1, 'A', Layer::Deep).is_ok());
+        assert!(Grid::new().fire(1, 'A', Layer::Deep).is_err());
+    }
+}
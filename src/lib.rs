@@ -0,0 +1,62 @@
+//!
+//! Classic Battleship: boards, ship placement, firing and computer opponents.
+//!
+//! Most programs only need the types re-exported here:
+//!     Grid holds one side's board and fleet. Ships are placed with set_computer_ships,
+//!         place_random_ship or place_ship, and fire returns the Outcome of each shot.
+//!     Difficulty picks a computer opponent, and AiSettings::opponent builds it as a
+//!         Guesser, which makes guesses and is told what they did.
+//!     Rules picks the rules and the board's Topology, and game_loop plays a whole game
+//!         with them on the terminal, under a TimeControl, as the battleship program does.
+//!
+
+mod clock;
+mod coord;
+mod difficulty;
+mod gamelogic;
+mod grid;
+mod layers;
+mod mines;
+mod mobile;
+mod monte_carlo;
+mod placement;
+mod possible_guess;
+mod profile;
+mod repair;
+mod rules;
+mod shapes;
+mod ship;
+mod sonar;
+mod summary;
+mod topology;
+mod weapons;
+
+//
+// Only used by the battleship program itself, for its command line: seeding, scripts,
+// benchmarks, saved stats and the theme. Nothing in the API above needs them, and they
+// are not part of the stable API.
+//
+#[doc(hidden)]
+pub mod rng;
+#[doc(hidden)]
+pub mod script;
+#[doc(hidden)]
+pub mod sim;
+#[doc(hidden)]
+pub mod stats;
+#[doc(hidden)]
+pub mod theme;
+
+pub use clock::TimeControl;
+pub use coord::{CoordState, Coordinate};
+pub use difficulty::{AiSettings, Difficulty};
+pub use gamelogic::game_loop;
+pub use grid::{Grid, Outcome, Shot, FLEET};
+pub use layers::Layer;
+pub use monte_carlo::MonteCarloGuesser;
+pub use placement::PlacementStyle;
+pub use possible_guess::{Guesser, PossibleGuesses};
+pub use rules::Rules;
+pub use shapes::Shape;
+pub use ship::{Fleet, Orientation, Ship, ShipId};
+pub use topology::Topology;
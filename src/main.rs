@@ -1,9 +1,64 @@
-use crate::gamelogic::game_loop;
-pub mod coord;
-pub mod gamelogic;
-pub mod grid;
-pub mod possible_guess;
+use crate::cli::{Command, USAGE};
+use battleship::script::Script;
+use battleship::sim::benchmark;
+use battleship::stats::StatsStore;
+use battleship::{game_loop, rng, theme, Difficulty, Rules};
+use std::{env, process};
+mod cli;
 
 fn main() {
-    game_loop();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli = match cli::parse(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    theme::set_theme(cli.theme);
+    if let Some(seed) = cli.seed {
+        rng::seed(seed);
+    }
+    let rules = Rules {
+        shaped: cli.shaped,
+        topology: cli.topology,
+        ..cli.rules
+    };
+
+    match cli.command {
+        Command::Help => print!("{USAGE}"),
+        Command::Stats => {
+            let stats = StatsStore::load(&StatsStore::default_path());
+            if stats.players.is_empty() {
+                println!("No games have been recorded yet.");
+            } else {
+                print!("{}", stats.tables());
+            }
+        }
+        Command::Sim(games) => {
+            let levels = match cli.difficulty {
+                Some(level) => vec![level],
+                None => Difficulty::all(),
+            };
+            for (level, average) in benchmark(&levels, games, rules) {
+                println!("{level:<10} {average:.1} shots to win");
+            }
+        }
+        Command::Replay(path) => {
+            let mut script = match Script::load(&path) {
+                Ok(script) => script,
+                Err(e) => {
+                    eprintln!("replay: {e}");
+                    process::exit(2);
+                }
+            };
+            if let Some(seed) = cli.seed {
+                script.seed = seed;
+            }
+            script.start();
+            game_loop(cli.difficulty, cli.placement, rules, cli.time_control);
+        }
+        Command::Play => game_loop(cli.difficulty, cli.placement, rules, cli.time_control),
+    }
 }
@@ -0,0 +1,230 @@
+use crate::coord::*;
+use crate::grid::Grid;
+use crate::layers::Layer;
+use crate::rng::random;
+use std::fmt::{self, Display};
+
+///
+/// How many mines each player places, with the mines rule
+///
+pub const MINES: usize = 2;
+
+///
+/// How many decoys each player places, with the mines rule
+///
+pub const DECOYS: usize = 2;
+
+///
+/// Something other than a ship that is placed on the board, with the mines rule
+///     Mine: whoever shoots it loses their next turn or gives away one of their ship points
+///     Decoy: reports a hit when it is shot, but never sinks
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Trap {
+    Mine,
+    Decoy,
+}
+
+impl Trap {
+    ///
+    /// Returns how many of the trap each player places
+    ///
+    pub fn count(&self) -> usize {
+        match self {
+            Trap::Mine => MINES,
+            Trap::Decoy => DECOYS,
+        }
+    }
+
+    ///
+    /// Returns how the trap is drawn on its owner's board
+    ///
+    pub fn state(&self) -> CoordState {
+        match self {
+            Trap::Mine => CoordState::Mine,
+            Trap::Decoy => CoordState::Decoy,
+        }
+    }
+}
+
+impl Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trap::Mine => f.pad("mine"),
+            Trap::Decoy => f.pad("decoy"),
+        }
+    }
+}
+
+///
+/// What shooting a mine does to the player who shot it
+///     LoseTurn: they miss their next turn
+///     Reveal: one of their ship points, this (row, column), is shown to the other player
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Blast {
+    LoseTurn,
+    Reveal((i32, char)),
+}
+
+impl Grid {
+    ///
+    /// Arguements:
+    ///     trap: Trap - a mine or a decoy
+    ///     point: (i32, char) - the (row, column) to put it on
+    ///     show: bool - whether to draw it on the board, for the player's own board
+    /// Returns an error saying why, if it can't go there: it is off the board, or there
+    /// is a ship or another trap there already
+    ///
+    pub(crate) fn place_trap(
+        &mut self,
+        trap: Trap,
+        point: (i32, char),
+        show: bool,
+    ) -> Result<(), String> {
+        if !on_board(point) {
+            return Err(format!("Your {trap} must be on the board"));
+        }
+        let rc = get_coord_index(point.0, point.1);
+        if self.fleet.ship_at(Layer::Surface, rc).is_some() {
+            return Err(format!("You can not place a {trap} on top of a ship."));
+        }
+        if self.mines.contains(&rc) || self.decoys.contains(&rc) {
+            return Err(format!(
+                "There is already a mine or decoy at {}{}",
+                point.1, point.0
+            ));
+        }
+        match trap {
+            Trap::Mine => self.mines.push(rc),
+            Trap::Decoy => self.decoys.push(rc),
+        }
+        if show {
+            self.grid[Layer::Surface.index()][rc.0][rc.1].state = trap.state();
+        }
+        Ok(())
+    }
+
+    ///
+    /// Places the computer's mines and decoys at random, on points without a ship.
+    /// They are not drawn on the board.
+    ///
+    pub(crate) fn set_computer_traps(&mut self) {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        for trap in [Trap::Mine, Trap::Decoy] {
+            for _ in 0..trap.count() {
+                let mut free = vec![];
+                for r in 0..10 {
+                    for (c, col) in columns.iter().enumerate() {
+                        let taken = self.fleet.ship_at(Layer::Surface, (r, c)).is_some()
+                            || self.mines.contains(&(r, c))
+                            || self.decoys.contains(&(r, c));
+                        if !taken {
+                            free.push((r as i32 + 1, *col));
+                        }
+                    }
+                }
+                let point = free[(random::<f32>() * free.len() as f32).floor() as usize];
+                self.place_trap(trap, point, false).unwrap();
+            }
+        }
+    }
+
+    ///
+    /// Asks the user where to put each of their mines and then each of their decoys,
+    /// after their ships are placed.
+    /// Loops until each one is put somewhere valid
+    ///
+    pub(crate) fn request_user_traps(&mut self) {
+        println!("{}", self);
+        for trap in [Trap::Mine, Trap::Decoy] {
+            for i in 1..=trap.count() {
+                println!(
+                    "Where do you want to put {trap} {i} of {}? For example: E5",
+                    trap.count()
+                );
+                loop {
+                    match get_input_coord() {
+                        (point, true) => match self.place_trap(trap, point, true) {
+                            Ok(()) => break,
+                            Err(e) => println!("{e}"),
+                        },
+                        _ => println!("Please enter a coordinate like E5"),
+                    }
+                }
+            }
+        }
+        println!("{}", self);
+    }
+
+    ///
+    /// Decides what a mine does to the player whose board this is, after they shot one:
+    /// at random, they lose their next turn or one of their ship points that hasn't
+    /// been hit is given away. If every ship point has been hit, they lose their turn.
+    ///
+    pub(crate) fn blast(&self) -> Blast {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let mut intact = vec![];
+        for ship in &self.fleet.ships {
+            for (i, &(r, c)) in ship.cells.iter().enumerate() {
+                if !ship.damage[i] {
+                    intact.push((r as i32 + 1, columns[c]));
+                }
+            }
+        }
+        if intact.is_empty() || random::<f32>() < 0.5 {
+            Blast::LoseTurn
+        } else {
+            Blast::Reveal(intact[(random::<f32>() * intact.len() as f32).floor() as usize])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Outcome;
+
+    #[test]
+    fn test_traps() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.create_ship(("Destroyer".to_string(), 2), &mut vec![(0, 0), (0, 1)])
+            .unwrap();
+        assert!(grid.place_trap(Trap::Mine, (1, 'A'), true).is_err());
+        assert!(grid.place_trap(Trap::Mine, (11, 'A'), true).is_err());
+        assert!(grid.place_trap(Trap::Mine, (5, 'E'), true).is_ok());
+        assert!(grid.place_trap(Trap::Decoy, (5, 'E'), true).is_err());
+        assert!(grid.place_trap(Trap::Decoy, (6, 'E'), false).is_ok());
+        assert_eq!(grid.grid[0][4][4].state, CoordState::Mine);
+        assert_eq!(grid.grid[0][5][4].state, CoordState::Blank);
+
+        assert_eq!(grid.fire(5, 'E', Layer::Surface), Ok(Outcome::Mine));
+        assert_eq!(grid.grid[0][4][4].state, CoordState::Detonated);
+        assert_eq!(grid.fire(5, 'E', Layer::Surface), Ok(Outcome::Miss));
+        assert_eq!(grid.fire(6, 'E', Layer::Surface), Ok(Outcome::Hit));
+        assert_eq!(grid.history[2].ship, None);
+
+        grid.fire(1, 'A', Layer::Surface).unwrap();
+        assert!(matches!(
+            grid.blast(),
+            Blast::LoseTurn | Blast::Reveal((1, 'B'))
+        ));
+        assert_eq!(grid.fire(1, 'B', Layer::Surface), Ok(Outcome::Won(2)));
+        assert_eq!(grid.blast(), Blast::LoseTurn);
+
+        let mut computer = Grid::new();
+        computer
+            .set_computer_ships(crate::placement::PlacementStyle::Random)
+            .unwrap();
+        computer.set_computer_traps();
+        assert_eq!(
+            (computer.mines.len(), computer.decoys.len()),
+            (MINES, DECOYS)
+        );
+        assert!(computer
+            .mines
+            .iter()
+            .all(|&point| computer.fleet.ship_at(Layer::Surface, point).is_none()));
+    }
+}
@@ -0,0 +1,249 @@
+use crate::coord::*;
+use crate::grid::{Direction, Grid};
+use crate::layers::Layer;
+use crate::ship::{Orientation, Ship};
+use crate::topology::Topology;
+
+impl Grid {
+    ///
+    /// Returns true if the ship lies down and to the left on a hex board, the third way
+    /// a straight line can run there
+    ///
+    fn is_diagonal(&self, ship: &Ship) -> bool {
+        self.topology == Topology::Hex
+            && ship.len() > 1
+            && ship.cells.windows(2).all(|pair| {
+                let (r0, c0) = pair[0];
+                let (r1, c1) = pair[1];
+                r1 == r0 + 1 && c0 == c1 + 1
+            })
+    }
+
+    ///
+    /// Moves a ship one point along its length, in the "mobile fleet" rules
+    /// Arguements:
+    ///     class: &str - the ship to move, like Cruiser
+    ///     direction: Direction - the way to move it, which has to be along the ship
+    /// Returns an error saying why, if the ship can't be moved: it has been hit, it isn't
+    /// straight, it would leave the board or it would move on top of another ship, a mine
+    /// or a decoy.
+    /// On a hex board a ship lying down and to the left moves up or down along its length.
+    /// The ship stays on its layer, and the points of it drawn there are redrawn where they
+    /// moved to
+    ///
+    pub(crate) fn move_ship(&mut self, class: &str, direction: Direction) -> Result<(), String> {
+        let ship = self
+            .fleet
+            .get(class)
+            .ok_or(format!("There is no {class} to move"))?;
+        if ship.hits() > 0 {
+            return Err(format!("The {class} has been hit, so it can't move"));
+        }
+        let diagonal = self.is_diagonal(ship);
+        if !ship.is_straight() && !diagonal {
+            return Err(format!("Only straight ships can move, not the {class}"));
+        }
+        let along = match ship.orientation {
+            _ if diagonal => [Direction::Up, Direction::Down].contains(&direction),
+            Orientation::Horizontal => [Direction::Left, Direction::Right].contains(&direction),
+            Orientation::Vertical => [Direction::Up, Direction::Down].contains(&direction),
+        };
+        if !along {
+            return Err(format!("The {class} can only move along its length"));
+        }
+
+        let (d_row, d_col) = match direction.step() {
+            (d_row, _) if diagonal => (d_row, -d_row),
+            step => step,
+        };
+        let mut cells = vec![];
+        for &(r, c) in &ship.cells {
+            let (r, c) = (r as i32 + d_row, c as i32 + d_col);
+            if !(0..10).contains(&r) || !(0..10).contains(&c) {
+                return Err(format!("The {class} can't move off the board"));
+            }
+            cells.push((r as usize, c as usize));
+        }
+        let (id, layer) = (ship.id, ship.layer);
+        let blocked = cells.iter().any(|&point| {
+            self.fleet
+                .ship_at(layer, point)
+                .is_some_and(|other| other.id != id)
+        });
+        if blocked {
+            return Err(format!("The {class} can not move on top of another ship"));
+        }
+        if layer == Layer::Surface
+            && cells
+                .iter()
+                .any(|point| self.mines.contains(point) || self.decoys.contains(point))
+        {
+            return Err(format!(
+                "The {class} can not move on top of a mine or decoy"
+            ));
+        }
+
+        let plane = &mut self.grid[layer.index()];
+        let shown: Vec<bool> = ship
+            .cells
+            .iter()
+            .map(|&(r, c)| plane[r][c].state == CoordState::Ship)
+            .collect();
+        for (i, &(r, c)) in ship.cells.iter().enumerate() {
+            if shown[i] {
+                plane[r][c].state = CoordState::Blank;
+            }
+        }
+        for (i, &(r, c)) in cells.iter().enumerate() {
+            if shown[i] {
+                plane[r][c].state = CoordState::Ship;
+            }
+        }
+        if let Some(ship) = self.fleet.ships.iter_mut().find(|ship| ship.id == id) {
+            ship.cells = cells;
+        }
+        Ok(())
+    }
+
+    ///
+    /// The computer's chance to dodge, in the "mobile fleet" rules
+    /// If the user's last guess at this board missed right next to a ship on the same layer
+    /// that hasn't been hit, that ship is moved one point along its length, away from the
+    /// guess if it can be. On a hex board next to means any of the six neighbours.
+    /// Only ships move_ship can move are looked at.
+    /// Arguements:
+    ///     shots: usize - how many guesses the board had before the user's turn, so a miss
+    ///         from an earlier turn is never dodged again
+    /// Returns true if a ship was moved, which uses up the computer's turn
+    ///
+    pub(crate) fn evade(&mut self, shots: usize) -> bool {
+        let Some(last) = self.history[shots..].last() else {
+            return false;
+        };
+        if last.report.0 != 'm' {
+            return false;
+        }
+        let (row, col) = get_coord_index(last.row, last.column);
+        let neighbors = self.topology.neighbors((row, col));
+        let (row, col) = (row as i32, col as i32);
+        let layer = last.layer;
+
+        let near = self.fleet.ships.iter().find(|ship| {
+            ship.layer == layer
+                && ship.hits() == 0
+                && (ship.is_straight() || self.is_diagonal(ship))
+                && ship.cells.iter().any(|point| neighbors.contains(point))
+        });
+        let Some(ship) = near else {
+            return false;
+        };
+
+        let (first_r, first_c) = ship.cells[0];
+        let (last_r, last_c) = ship.cells[ship.len() - 1];
+        let away = match ship.orientation {
+            _ if self.is_diagonal(ship) && 2 * row < (first_r + last_r) as i32 => {
+                [Direction::Down, Direction::Up]
+            }
+            _ if self.is_diagonal(ship) => [Direction::Up, Direction::Down],
+            Orientation::Horizontal if 2 * col < (first_c + last_c) as i32 => {
+                [Direction::Right, Direction::Left]
+            }
+            Orientation::Horizontal => [Direction::Left, Direction::Right],
+            Orientation::Vertical if 2 * row < (first_r + last_r) as i32 => {
+                [Direction::Down, Direction::Up]
+            }
+            Orientation::Vertical => [Direction::Up, Direction::Down],
+        };
+        let class = ship.class.clone();
+        for direction in away {
+            if self.move_ship(&class, direction).is_ok() {
+                if !self.quiet {
+                    println!("The computer moved one of its ships");
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_ship() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        let mut points: Vec<(usize, usize)> =
+            (0..10).flat_map(|r| (0..10).map(move |c| (r, c))).collect();
+        grid.place_user_ship("Destroyer".to_string(), (1, 'A'), (1, 'B'), 2, &mut points);
+        grid.place_user_ship("Cruiser".to_string(), (2, 'C'), (4, 'C'), 3, &mut points);
+
+        assert!(grid.move_ship("Destroyer", Direction::Left).is_err());
+        assert!(grid.move_ship("Destroyer", Direction::Down).is_err());
+        assert!(grid.move_ship("Carrier", Direction::Down).is_err());
+        assert!(grid.move_ship("Destroyer", Direction::Right).is_ok());
+        assert_eq!(
+            grid.fleet.get("Destroyer").unwrap().cells,
+            vec![(0, 1), (0, 2)]
+        );
+        assert_eq!(grid.grid[0][0][0].state, CoordState::Blank);
+        assert_eq!(grid.grid[0][0][2].state, CoordState::Ship);
+
+        assert!(grid.move_ship("Cruiser", Direction::Up).is_err());
+        grid.guess(2, 'C');
+        assert!(grid.move_ship("Cruiser", Direction::Down).is_err());
+
+        // on a hex board a ship lying down and to the left moves along that line
+        grid.topology = Topology::Hex;
+        grid.place_user_ship("Submarine".to_string(), (5, 'J'), (7, 'H'), 3, &mut points);
+        assert!(grid.move_ship("Submarine", Direction::Right).is_err());
+        assert!(grid.move_ship("Submarine", Direction::Down).is_ok());
+        assert_eq!(
+            grid.fleet.get("Submarine").unwrap().cells,
+            vec![(5, 8), (6, 7), (7, 6)]
+        );
+    }
+
+    #[test]
+    fn test_evade() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.create_ship(
+            ("Cruiser".to_string(), 3),
+            &mut vec![(4, 3), (4, 4), (4, 5)],
+        )
+        .unwrap();
+        assert!(!grid.evade(0));
+
+        // a near miss on the left end pushes the cruiser right
+        grid.guess(5, 'C');
+        assert!(grid.evade(0));
+        assert_eq!(grid.fleet.get("Cruiser").unwrap().cells[0], (4, 4));
+        // the same miss isn't dodged again on a turn the user didn't fire
+        assert!(!grid.evade(1));
+
+        grid.guess(1, 'A');
+        assert!(!grid.evade(1));
+    }
+
+    #[test]
+    fn test_evade_hex() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.topology = Topology::Hex;
+        let mut points: Vec<(usize, usize)> =
+            (0..10).flat_map(|r| (0..10).map(move |c| (r, c))).collect();
+        grid.place_user_ship("Cruiser".to_string(), (5, 'G'), (7, 'E'), 3, &mut points);
+
+        // up and to the right of the top end only touches it on a hex board, and the
+        // cruiser lying down and to the left moves down, away from it
+        grid.guess(4, 'H');
+        assert!(grid.evade(0));
+        assert_eq!(
+            grid.fleet.get("Cruiser").unwrap().cells,
+            vec![(5, 5), (6, 4), (7, 3)]
+        );
+    }
+}
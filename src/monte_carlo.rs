@@ -0,0 +1,551 @@
+use crate::coord::*;
+use crate::difficulty::{DEFAULT_SAMPLES, DEFAULT_TIME_BUDGET_MS};
+use crate::grid::FLEET;
+use crate::possible_guess::{Guesser, HABIT_WEIGHT, NO_POINTS_LEFT};
+use crate::rng::{self, random};
+use crate::rules::Rules;
+use crate::shapes::{shape_of, shapes_for, Shape};
+use crate::sonar::best_scan_center;
+use crate::topology::Topology;
+use std::{
+    char,
+    time::{Duration, Instant},
+};
+
+///
+/// The strongest computer opponent.
+/// Every guess it samples full layouts of the ships that are still afloat that agree
+/// with everything it has seen so far, and fires at the point that is covered most often.
+///
+/// board holds what has been seen at each (row, column): Blank for unknown, Miss, or Hit
+///     for a hit that does not belong to a sunk ship yet
+/// sunk holds the points of ships that have been sunk, which no other ship can use
+/// remaining_ships holds the lengths of the ships that have not been sunk yet
+/// scans holds the (row, column) points of each sonar scan and how many of them had a ship
+///     on them. Layouts that disagree with a scan are not counted.
+/// revealed holds the (row, column) of ship points the player gave away by shooting a mine
+/// samples is the most layouts to sample for one guess
+/// time_budget is the most time to spend sampling for one guess
+/// guess_count keeps track of how many guesses have been made
+/// heatmap is where the player has put ships in past games, if it is known
+/// recheck holds the (row, column) of the hits to fire at again, after the player
+///     repaired one of them
+/// repairs is how many repaired hits haven't been found again yet
+/// shaped is true if the player's ships are shaped like SHAPED_FLEET
+/// topology is the board being guessed at
+///
+pub struct MonteCarloGuesser {
+    pub board: Vec<Vec<CoordState>>,
+    pub sunk: Vec<(usize, usize)>,
+    pub remaining_ships: Vec<i32>,
+    pub scans: Vec<(Vec<(usize, usize)>, usize)>,
+    pub revealed: Vec<(usize, usize)>,
+    pub samples: usize,
+    pub time_budget: Duration,
+    pub guess_count: i32,
+    pub heatmap: Option<Vec<Vec<f32>>>,
+    pub recheck: Vec<(usize, usize)>,
+    pub repairs: i32,
+    pub shaped: bool,
+    pub topology: Topology,
+}
+
+impl MonteCarloGuesser {
+    ///
+    /// Creates a new MonteCarloGuesser with nothing seen yet
+    /// Arguements:
+    ///     samples: usize - the most layouts to sample for one guess
+    ///     time_budget: Duration - the most time to spend sampling for one guess
+    ///
+    pub fn new(samples: usize, time_budget: Duration) -> Self {
+        Self {
+            board: vec![vec![CoordState::Blank; 10]; 10],
+            sunk: vec![],
+            remaining_ships: FLEET.iter().map(|&(_, length)| length as i32).collect(),
+            scans: vec![],
+            revealed: vec![],
+            samples,
+            time_budget,
+            guess_count: 0,
+            heatmap: None,
+            recheck: vec![],
+            repairs: 0,
+            shaped: false,
+            topology: Topology::Square,
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     shape: &Shape - the shape of the ship
+    ///     taken: &[Vec<bool>] - points that can not be used, misses, sunk ships and ships
+    ///         already placed in this sample
+    /// Returns every placement of the ship, any way it can lie, that only uses
+    /// points that are not taken. For a straight ship that is along the board's axes.
+    ///
+    fn placements(&self, shape: &Shape, taken: &[Vec<bool>]) -> Vec<Vec<(usize, usize)>> {
+        let free: Vec<Vec<bool>> = taken
+            .iter()
+            .map(|row| row.iter().map(|&taken| !taken).collect())
+            .collect();
+        shape.placements(&free, self.topology)
+    }
+
+    ///
+    /// Samples one layout of the remaining ships that agrees with the board.
+    /// Ships are first placed over each unresolved hit, so that every hit is
+    /// covered, then the rest of the ships are placed anywhere they fit.
+    /// A hit that no ship left can cover is left uncovered, since it can only be a decoy.
+    /// Returns the points covered by the layout, or None if this attempt got stuck
+    ///
+    fn sample_layout(&self) -> Option<Vec<(usize, usize)>> {
+        let mut taken = vec![vec![false; 10]; 10];
+        for (r, row) in self.board.iter().enumerate() {
+            for (c, state) in row.iter().enumerate() {
+                if *state == CoordState::Miss {
+                    taken[r][c] = true;
+                }
+            }
+        }
+        for &(r, c) in &self.sunk {
+            taken[r][c] = true;
+        }
+        let seen = taken.clone();
+
+        let mut hits = vec![];
+        for (r, row) in self.board.iter().enumerate() {
+            for (c, state) in row.iter().enumerate() {
+                if *state == CoordState::Hit {
+                    hits.push((r, c));
+                }
+            }
+        }
+
+        let mut ships = shapes_for(&self.remaining_ships, self.shaped);
+        let mut layout: Vec<(usize, usize)> = vec![];
+
+        while !hits.is_empty() {
+            let hit = hits.remove((random::<f32>() * hits.len() as f32).floor() as usize);
+            if layout.contains(&hit) {
+                continue;
+            }
+            let mut options = vec![];
+            for (i, shape) in ships.iter().enumerate() {
+                for ship in self.placements(shape, &taken) {
+                    if ship.contains(&hit) {
+                        options.push((i, ship));
+                    }
+                }
+            }
+            if options.is_empty() {
+                let decoy = shapes_for(&self.remaining_ships, self.shaped)
+                    .iter()
+                    .all(|shape| {
+                        self.placements(shape, &seen)
+                            .iter()
+                            .all(|ship| !ship.contains(&hit))
+                    });
+                if decoy {
+                    continue;
+                }
+                return None;
+            }
+            let (i, ship) =
+                options.remove((random::<f32>() * options.len() as f32).floor() as usize);
+            ships.remove(i);
+            for &(r, c) in &ship {
+                taken[r][c] = true;
+            }
+            layout.extend(ship);
+        }
+
+        for shape in ships {
+            let mut options = self.placements(&shape, &taken);
+            if options.is_empty() {
+                return None;
+            }
+            let ship = options.remove((random::<f32>() * options.len() as f32).floor() as usize);
+            for &(r, c) in &ship {
+                taken[r][c] = true;
+            }
+            layout.extend(ship);
+        }
+        Some(layout)
+    }
+
+    ///
+    /// Returns true if the layout, along with the sunk ships, has as many points in each
+    /// sonar scan as the scan found
+    ///
+    fn agrees_with_scans(&self, layout: &[(usize, usize)]) -> bool {
+        self.scans.iter().all(|(area, count)| {
+            let found = area
+                .iter()
+                .filter(|point| layout.contains(point) || self.sunk.contains(point))
+                .count();
+            found == *count
+        })
+    }
+
+    ///
+    /// Returns how many sampled layouts covered each (row, column) that has not been
+    /// guessed yet. Layouts that disagree with a sonar scan are left out. Sampling stops
+    /// at self.samples layouts or once self.time_budget runs out.
+    /// The time budget is ignored when the game is seeded, so a seeded game always samples
+    /// the same layouts however fast the computer is.
+    ///
+    pub fn counts(&self) -> Vec<Vec<i32>> {
+        let mut counts = vec![vec![0; 10]; 10];
+        let start = Instant::now();
+
+        for _ in 0..self.samples {
+            if !rng::is_seeded() && start.elapsed() > self.time_budget {
+                break;
+            }
+            if let Some(layout) = self
+                .sample_layout()
+                .filter(|layout| self.agrees_with_scans(layout))
+            {
+                for (r, c) in layout {
+                    if self.board[r][c] == CoordState::Blank {
+                        counts[r][c] += 1;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    ///
+    /// Arguements:
+    ///     point: (usize, usize) - the (row, column) of the hit that sunk the ship
+    ///     length: i32 - the length of the sunk ship
+    /// Finds the hits the sunk ship must have been, in the shape of a ship of that
+    /// length, and moves those points from the board's hits into self.sunk.
+    /// If no shape of hits fits, only the point itself is moved.
+    ///
+    fn resolve_sunk(&mut self, point: (usize, usize), length: i32) {
+        let hits: Vec<Vec<bool>> = self
+            .board
+            .iter()
+            .map(|row| row.iter().map(|state| *state == CoordState::Hit).collect())
+            .collect();
+        for (name, _) in FLEET.iter().filter(|&&(_, l)| l as i32 == length) {
+            let ship = shape_of(name, self.shaped)
+                .placements(&hits, self.topology)
+                .into_iter()
+                .find(|ship| ship.contains(&point));
+            if let Some(ship) = ship {
+                for (r, c) in ship {
+                    self.board[r][c] = CoordState::Blank;
+                    self.sunk.push((r, c));
+                }
+                return;
+            }
+        }
+        self.board[point.0][point.1] = CoordState::Blank;
+        self.sunk.push(point);
+    }
+}
+
+impl Default for MonteCarloGuesser {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SAMPLES,
+            Duration::from_millis(DEFAULT_TIME_BUDGET_MS),
+        )
+    }
+}
+
+impl Guesser for MonteCarloGuesser {
+    ///
+    /// Fires at the point with the highest count (ties are random).
+    /// If there is a heatmap, each count is weighted by how often the player has
+    /// put a ship there before.
+    /// If no layout could be sampled in time, fires at a random unguessed point.
+    /// A ship point given away by a mine is fired at first, after any hits that have to
+    /// be fired at again because of a repair.
+    /// Returns an error if every point has been ruled out.
+    ///
+    fn next_guess(&mut self) -> Result<(char, i32), String> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        self.guess_count += 1;
+        if let Some(&(r, c)) = self.recheck.first() {
+            return Ok((columns[c], r as i32 + 1));
+        }
+        let revealed = self
+            .revealed
+            .iter()
+            .find(|&&(r, c)| self.board[r][c] == CoordState::Blank && !self.sunk.contains(&(r, c)));
+        if let Some(&(r, c)) = revealed {
+            return Ok((columns[c], r as i32 + 1));
+        }
+        let mut counts: Vec<Vec<f32>> = self
+            .counts()
+            .iter()
+            .map(|row| row.iter().map(|&count| count as f32).collect())
+            .collect();
+        if let Some(heatmap) = &self.heatmap {
+            for r in 0..10 {
+                for c in 0..10 {
+                    counts[r][c] *= 1. + HABIT_WEIGHT * heatmap[r][c];
+                }
+            }
+        }
+
+        let mut open = vec![];
+        for r in 0..10 {
+            for c in 0..10 {
+                if self.board[r][c] == CoordState::Blank && !self.sunk.contains(&(r, c)) {
+                    open.push((r, c));
+                }
+            }
+        }
+        let highest = open.iter().map(|&(r, c)| counts[r][c]).fold(0., f32::max);
+        let best: Vec<(usize, usize)> = open
+            .into_iter()
+            .filter(|&(r, c)| counts[r][c] == highest)
+            .collect();
+        if best.is_empty() {
+            return Err(NO_POINTS_LEFT.to_string());
+        }
+
+        let (r, c) = best[(random::<f32>() * best.len() as f32).floor() as usize];
+        Ok((columns[c], r as i32 + 1))
+    }
+
+    ///
+    /// Records a miss or hit on the board.
+    /// When a ship is sunk its length is removed from remaining_ships and its points
+    /// are moved into sunk
+    /// A hit fired at again after a repair stays a hit, whatever the report. Only one that
+    /// was hit again, rather than reported still hit, is a repaired hit found. Once every
+    /// repaired hit has been found, the rest of recheck is dropped.
+    ///
+    fn record_guess(&mut self, report: (char, (char, i32)), col: char, row: i32) {
+        let (hit, (sunk, length)) = report;
+        let (r, c) = get_coord_index(row, col);
+        if let Some(pos) = self.recheck.iter().position(|&point| point == (r, c)) {
+            self.recheck.remove(pos);
+            if hit == 'h' && sunk != 'a' {
+                self.repairs -= 1;
+            }
+            if self.repairs <= 0 || self.recheck.is_empty() {
+                self.repairs = 0;
+                self.recheck.clear();
+            }
+        }
+
+        if hit == 'h' {
+            self.board[r][c] = CoordState::Hit;
+            if sunk == 's' || sunk == 'f' {
+                if let Some(pos) = self.remaining_ships.iter().position(|&l| l == length) {
+                    self.remaining_ships.remove(pos);
+                }
+                self.resolve_sunk((r, c), length);
+            }
+        } else if self.board[r][c] == CoordState::Blank && !self.sunk.contains(&(r, c)) {
+            self.board[r][c] = CoordState::Miss;
+        }
+    }
+
+    fn learn_habits(&mut self, heatmap: Vec<Vec<f32>>) {
+        self.heatmap = Some(heatmap);
+    }
+
+    ///
+    /// Samples layouts of only these ships
+    ///
+    fn expect_ships(&mut self, lengths: &[i32]) {
+        self.remaining_ships = lengths.to_vec();
+    }
+
+    ///
+    /// Samples layouts with the shapes the rules give the player's ships, on the rules'
+    /// board
+    ///
+    fn play_by(&mut self, rules: Rules) {
+        self.shaped = rules.shaped;
+        self.topology = rules.topology;
+    }
+
+    ///
+    /// Only scans while there are no unresolved hits, aimed where the scan covers the
+    /// most unguessed points
+    ///
+    fn scan_target(&mut self) -> Option<(char, i32)> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        if self
+            .board
+            .iter()
+            .flatten()
+            .any(|&state| state == CoordState::Hit)
+        {
+            return None;
+        }
+        let mut open = vec![];
+        for (r, row) in self.board.iter().enumerate() {
+            for (c, state) in row.iter().enumerate() {
+                if *state == CoordState::Blank && !self.sunk.contains(&(r, c)) {
+                    open.push((columns[c], r as i32 + 1));
+                }
+            }
+        }
+        best_scan_center(&open)
+    }
+
+    ///
+    /// A ship that moved one point can only have moved onto a miss next to an unknown
+    /// point, so those misses are made unknown again. Earlier sonar scans may no longer
+    /// be right, so they are forgotten.
+    ///
+    fn opponent_moved(&mut self) {
+        let mut stale = vec![];
+        for r in 0..10 {
+            for c in 0..10 {
+                let unknown_next_to = self.topology.neighbors((r, c)).iter().any(|&(r, c)| {
+                    self.board[r][c] == CoordState::Blank && !self.sunk.contains(&(r, c))
+                });
+                if self.board[r][c] == CoordState::Miss && unknown_next_to {
+                    stale.push((r, c));
+                }
+            }
+        }
+        for (r, c) in stale {
+            self.board[r][c] = CoordState::Blank;
+        }
+        self.scans.clear();
+    }
+
+    ///
+    /// The repaired hit is one of the hits that don't belong to a sunk ship, so they are
+    /// all fired at again. They stay hits on the board, since there is a ship there either
+    /// way.
+    ///
+    fn opponent_repaired(&mut self) {
+        self.repairs += 1;
+        for r in 0..10 {
+            for c in 0..10 {
+                if self.board[r][c] == CoordState::Hit && !self.recheck.contains(&(r, c)) {
+                    self.recheck.push((r, c));
+                }
+            }
+        }
+    }
+
+    ///
+    /// Keeps the point, to fire at next
+    ///
+    fn reveal(&mut self, col: char, row: i32) {
+        self.revealed.push(get_coord_index(row, col));
+    }
+
+    ///
+    /// Keeps the scan, so sampled layouts have to agree with it.
+    /// If it found no ships, its unknown points are marked as misses
+    ///
+    fn record_scan(&mut self, area: &[(char, i32)], count: usize) {
+        let area: Vec<(usize, usize)> = area
+            .iter()
+            .map(|&(col, row)| get_coord_index(row, col))
+            .collect();
+        if count == 0 {
+            for &(r, c) in &area {
+                if self.board[r][c] == CoordState::Blank {
+                    self.board[r][c] = CoordState::Miss;
+                }
+            }
+        }
+        self.scans.push((area, count));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follows_up_hit() {
+        let mut mc = MonteCarloGuesser::new(500, Duration::from_secs(10));
+        mc.record_guess(('h', ('u', 0)), 'E', 5);
+        mc.record_guess(('m', ('n', 0)), 'E', 4);
+        let guess = mc.next_guess().unwrap();
+        assert!([('D', 5), ('F', 5), ('E', 6)].contains(&guess));
+        assert_eq!(mc.guess_count, 1);
+    }
+
+    #[test]
+    fn test_sunk_ship() {
+        let mut mc = MonteCarloGuesser::new(10, Duration::from_secs(10));
+        mc.record_guess(('h', ('u', 0)), 'B', 3);
+        mc.record_guess(('h', ('u', 0)), 'B', 5);
+        mc.record_guess(('h', ('s', 3)), 'B', 4);
+        assert_eq!(mc.remaining_ships, vec![5, 4, 3, 2]);
+        assert_eq!(mc.sunk.len(), 3);
+        assert!(mc.sunk.contains(&(2, 1)));
+        assert!(mc.sunk.contains(&(4, 1)));
+        assert!(mc.board.iter().flatten().all(|s| *s != CoordState::Hit));
+
+        let counts = mc.counts();
+        assert_eq!(counts[3][1], 0);
+        assert!(counts.iter().flatten().any(|&c| c > 0));
+
+        // a hit with misses all around can only be a decoy
+        mc.record_guess(('h', ('u', 0)), 'H', 8);
+        for (col, row) in [('G', 8), ('I', 8), ('H', 7), ('H', 9)] {
+            mc.record_guess(('m', ('n', 0)), col, row);
+        }
+        assert!(mc.counts().iter().flatten().any(|&c| c > 0));
+        mc.reveal('A', 10);
+        assert_eq!(mc.next_guess(), Ok(('A', 10)));
+
+        // once every point is ruled out there is nothing left to fire at
+        let mut mc = MonteCarloGuesser::new(10, Duration::from_millis(10));
+        mc.board = vec![vec![CoordState::Miss; 10]; 10];
+        assert!(mc.next_guess().is_err());
+    }
+
+    #[test]
+    fn test_opponent_repaired() {
+        let mut mc = MonteCarloGuesser::new(10, Duration::from_secs(10));
+        mc.record_guess(('h', ('u', 0)), 'D', 4);
+        mc.record_guess(('h', ('u', 0)), 'D', 5);
+        mc.opponent_repaired();
+        assert_eq!(mc.next_guess(), Ok(('D', 4)));
+
+        // D4 is still hit, so the repair is still to be found at D5
+        mc.record_guess(('h', ('a', 0)), 'D', 4);
+        assert_eq!(mc.board[3][3], CoordState::Hit);
+        assert_eq!(mc.repairs, 1);
+        assert_eq!(mc.next_guess(), Ok(('D', 5)));
+        mc.record_guess(('h', ('u', 0)), 'D', 5);
+        assert_eq!(mc.repairs, 0);
+        assert!(mc.recheck.is_empty());
+    }
+
+    #[test]
+    fn test_record_scan() {
+        let mut mc = MonteCarloGuesser::new(2000, Duration::from_secs(10));
+        assert!(mc.scan_target().is_some());
+        mc.record_scan(&[('A', 1), ('B', 1)], 0);
+        assert_eq!(mc.board[0][1], CoordState::Miss);
+
+        // every ship left has to cross the middle of the top row
+        let top: Vec<(char, i32)> = ['C', 'D', 'E', 'F', 'G', 'H', 'I', 'J']
+            .iter()
+            .map(|&col| (col, 1))
+            .collect();
+        mc.remaining_ships = vec![2];
+        mc.record_scan(&top, 2);
+        let counts = mc.counts();
+        assert!(counts[1..].iter().flatten().all(|&c| c == 0));
+        assert!(counts[0][2..].iter().any(|&c| c > 0));
+
+        mc.record_guess(('h', ('u', 0)), 'E', 1);
+        assert!(mc.scan_target().is_none());
+
+        mc.opponent_moved();
+        assert!(mc.scans.is_empty());
+        assert_eq!(mc.board[0][1], CoordState::Blank);
+    }
+}
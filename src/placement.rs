@@ -0,0 +1,258 @@
+use crate::shapes::Shape;
+use crate::ship::Fleet;
+use crate::topology::Topology;
+use std::fmt::{self, Display};
+
+///
+/// How many random layouts the adversarial placement compares
+///
+pub const ADVERSARIAL_LAYOUTS: usize = 200;
+
+///
+/// How the computer places its ships
+///     Random: any legal layout, picked at random
+///     Adversarial: the layout, out of many random ones, that common hunting
+///         patterns take the most shots to find
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlacementStyle {
+    #[default]
+    Random,
+    Adversarial,
+}
+
+impl PlacementStyle {
+    ///
+    /// Every style, in the order they are shown in the menu
+    ///
+    pub fn all() -> Vec<PlacementStyle> {
+        vec![PlacementStyle::Random, PlacementStyle::Adversarial]
+    }
+
+    ///
+    /// Arguements:
+    ///     input: &str - either the style's name or its number in the menu (starting at 1)
+    /// Returns the matching style, or None if the input is not a style.
+    /// Names are not case sensitive.
+    ///
+    pub fn from_input(input: &str) -> Option<PlacementStyle> {
+        let input = input.trim().to_lowercase();
+        let styles = PlacementStyle::all();
+
+        if let Ok(number) = input.parse::<usize>() {
+            if (1..=styles.len()).contains(&number) {
+                return Some(styles[number - 1]);
+            }
+            return None;
+        }
+        styles
+            .into_iter()
+            .find(|style| style.to_string().to_lowercase() == input)
+    }
+}
+
+impl Display for PlacementStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlacementStyle::Random => f.pad("Random"),
+            PlacementStyle::Adversarial => f.pad("Adversarial"),
+        }
+    }
+}
+
+///
+/// Arguements:
+///     scores: &[Vec<f32>] - how attractive each (row, column) is to a hunter, which
+///         guesses the highest scores first and points with equal scores in a random order
+///     fleet: &Fleet - the layout
+/// Returns how many guesses the hunter makes, on average, before it has hit every ship.
+///
+/// That is the sum, over every number of guesses n, of the chance some ship is still
+/// missed after n guesses. After n guesses every point scored higher than the current
+/// score s has been guessed, along with a random few of the points scored s. The chance
+/// every ship was hit comes from adding up, with alternating signs, the chance each set
+/// of ships was missed completely, which for k of the ships' points scored s is the
+/// chance none of the k were among the ones guessed so far.
+///
+fn shots_to_find(scores: &[Vec<f32>], fleet: &Fleet) -> f32 {
+    let mut all: Vec<f32> = scores.iter().flatten().copied().collect();
+    all.sort_by(|a, b| b.total_cmp(a));
+    let ships: Vec<Vec<f32>> = fleet
+        .ships
+        .iter()
+        .map(|ship| {
+            ship.cells
+                .iter()
+                .map(|&(row, col)| scores[row][col])
+                .collect()
+        })
+        .collect();
+
+    // for each set of ships, the best score on any of their points, how many points have
+    // it, and the sign the set is added with
+    let sets: Vec<(f32, i32, f32)> = (0..1u32 << ships.len())
+        .map(|set| {
+            let cells: Vec<f32> = (0..ships.len())
+                .filter(|ship| set & (1 << ship) != 0)
+                .flat_map(|ship| ships[ship].clone())
+                .collect();
+            let best = cells.iter().copied().fold(f32::MIN, f32::max);
+            let on_best = cells.iter().filter(|&&score| score == best).count() as i32;
+            let sign = if set.count_ones() % 2 == 0 { 1. } else { -1. };
+            (best, on_best, sign)
+        })
+        .collect();
+
+    let mut expected = 0.;
+    for (n, &current) in all.iter().enumerate() {
+        let higher = all.iter().filter(|&&score| score > current).count() as i32;
+        let tied = all.iter().filter(|&&score| score == current).count() as i32;
+        let guessed = n as i32 - higher;
+
+        let mut all_hit = 0.;
+        for &(best, on_best, sign) in &sets {
+            let missed: f32 = if best > current {
+                0.
+            } else if best < current {
+                1.
+            } else {
+                (0..on_best)
+                    .map(|i| ((tied - guessed - i) as f32 / (tied - i) as f32).max(0.))
+                    .product()
+            };
+            all_hit += sign * missed;
+        }
+        expected += 1. - all_hit;
+    }
+    expected
+}
+
+///
+/// Returns how many placements of the fleet's ships, in their shapes, cover each
+/// (row, column) on an empty board of the topology. This is what a density hunter
+/// starts with.
+///
+fn empty_board_density(fleet: &Fleet, topology: Topology) -> Vec<Vec<f32>> {
+    let mut density = vec![vec![0.; 10]; 10];
+    let free = vec![vec![true; 10]; 10];
+    for ship in &fleet.ships {
+        let cells: Vec<(i32, i32)> = ship
+            .cells
+            .iter()
+            .map(|&(r, c)| (r as i32, c as i32))
+            .collect();
+        for placement in Shape::new(&cells).placements(&free, topology) {
+            for (row, col) in placement {
+                density[row][col] += 1.;
+            }
+        }
+    }
+    density
+}
+
+///
+/// Arguements:
+///     fleet: &Fleet - the layout, as in Grid::fleet
+///     topology: Topology - the board it is laid out on
+/// Returns how many shots, on average, common hunting patterns need to hit every
+/// ship in the layout, see shots_to_find. Higher is better for the player who placed it.
+///
+/// Three hunters are scored:
+///     checkerboard: guesses every point of one colour, where row + column is even
+///         (or odd), in a random order before the other colour. Each colour is scored
+///         on its own, and the colour that finds the layout faster is kept.
+///     center: guesses the points closest to the middle of the board first
+///     density: guesses the points the most ship placements cover first
+/// The player could be hunting any of these ways, so the layout's score is the average
+/// of the three: the shots a hunter picked at random needs on average. A layout has to
+/// hold up against all three to score well, but one hunter's ties don't decide it.
+///
+pub fn hunt_score(fleet: &Fleet, topology: Topology) -> f32 {
+    let checkerboard = (0..2)
+        .map(|colour| {
+            let scores: Vec<Vec<f32>> = (0..10)
+                .map(|row| {
+                    (0..10)
+                        .map(|col| if (row + col) % 2 == colour { 1. } else { 0. })
+                        .collect()
+                })
+                .collect();
+            shots_to_find(&scores, fleet)
+        })
+        .fold(f32::MAX, f32::min);
+
+    let closeness: Vec<Vec<f32>> = (0..10)
+        .map(|row| {
+            (0..10)
+                .map(|col| {
+                    let d_row = row as f32 - 4.5;
+                    let d_col = col as f32 - 4.5;
+                    -(d_row * d_row + d_col * d_col)
+                })
+                .collect()
+        })
+        .collect();
+    let center = shots_to_find(&closeness, fleet);
+
+    let density = shots_to_find(&empty_board_density(fleet, topology), fleet);
+
+    (checkerboard + center + density) / 3.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::rng;
+
+    #[test]
+    fn test_from_input() {
+        assert_eq!(
+            PlacementStyle::from_input("1"),
+            Some(PlacementStyle::Random)
+        );
+        assert_eq!(
+            PlacementStyle::from_input("Adversarial\n"),
+            Some(PlacementStyle::Adversarial)
+        );
+        assert_eq!(PlacementStyle::from_input("3"), None);
+    }
+
+    #[test]
+    fn test_hunt_score() {
+        let mut middle = Fleet::new();
+        middle.add("Destroyer", vec![(4, 4), (4, 5)]);
+        let mut edge = Fleet::new();
+        edge.add("Destroyer", vec![(0, 4), (0, 5)]);
+        assert!(hunt_score(&edge, Topology::Square) > hunt_score(&middle, Topology::Square));
+
+        // a hunter that likes the corner most hits a ship there first
+        let mut corner = Fleet::new();
+        corner.add("Destroyer", vec![(0, 0), (0, 1)]);
+        let mut scores = vec![vec![0.; 10]; 10];
+        scores[0][1] = 1.;
+        assert_eq!(shots_to_find(&scores, &corner), 1.);
+
+        // full fleets score differently, and the adversarial layout beats random ones
+        rng::seed(31);
+        let random_scores: Vec<f32> = (0..50)
+            .map(|_| {
+                let mut grid = Grid::new();
+                grid.set_computer_ships(PlacementStyle::Random).unwrap();
+                hunt_score(&grid.fleet, Topology::Square)
+            })
+            .collect();
+        let mut distinct = random_scores.clone();
+        distinct.sort_by(f32::total_cmp);
+        distinct.dedup();
+        assert!(distinct.len() > 25);
+        let average = random_scores.iter().sum::<f32>() / random_scores.len() as f32;
+
+        let mut grid = Grid::new();
+        grid.set_computer_ships(PlacementStyle::Adversarial)
+            .unwrap();
+        let adversarial = hunt_score(&grid.fleet, Topology::Square);
+        assert!(adversarial > average);
+        assert!(adversarial >= distinct[distinct.len() / 2]);
+    }
+}
@@ -1,6 +1,68 @@
-use rand::random;
+use crate::coord::get_coord_index;
+use crate::difficulty::*;
+use crate::grid::FLEET;
+use crate::rng::random;
+use crate::rules::Rules;
+use crate::shapes::shapes_for;
+use crate::sonar::best_scan_center;
+use crate::topology::Topology;
 use std::char;
 
+///
+/// The interface every computer opponent makes its guesses through
+/// next_guess returns the (col: char, row: i32) to guess next, or an error if there is
+///     nothing left to guess
+/// record_guess is told the report from every guess that was made, in the format
+///     returned by Grid::guess
+///
+/// learn_habits is given a heatmap of where the player has put ships in past games,
+///     indexed by (row, column), to bias hunting towards. It is ignored by default.
+/// return_guess is given back a guess from next_guess that was never fired at, so it
+///     can be guessed again later. It is ignored by default.
+/// scan_target returns the (col, row) to aim a sonar scan at this turn, or None to
+///     fire instead. By default the computer never scans.
+/// record_scan is told the (col, row) of every point a sonar scan covered and how many
+///     of them have a ship on them, hit or not. It is ignored by default.
+/// opponent_moved is told when the other player moved a ship that hadn't been hit, so
+///     misses next to points that haven't been guessed may have a ship on them now.
+///     It is ignored by default.
+/// reveal is told the (col, row) of a ship point the other player gave away by shooting
+///     a mine. It is ignored by default.
+/// expect_ships is told the lengths of the only ships on the board it is guessing at,
+///     when the other player's fleet is split between layers. It is ignored by default.
+/// opponent_repaired is told when the other player repaired a hit on a ship that isn't
+///     sunk, but not where, so one of the hits that doesn't belong to a sunk ship has to
+///     be guessed again. It is ignored by default.
+/// play_by is told the rules of the game before it starts, which say how the other
+///     player's ships are shaped and what board they are on. It is ignored by default.
+///
+pub trait Guesser {
+    fn next_guess(&mut self) -> Result<(char, i32), String>;
+    fn record_guess(&mut self, report: (char, (char, i32)), col: char, row: i32);
+    fn learn_habits(&mut self, _heatmap: Vec<Vec<f32>>) {}
+    fn return_guess(&mut self, _col: char, _row: i32) {}
+    fn scan_target(&mut self) -> Option<(char, i32)> {
+        None
+    }
+    fn record_scan(&mut self, _area: &[(char, i32)], _count: usize) {}
+    fn opponent_moved(&mut self) {}
+    fn reveal(&mut self, _col: char, _row: i32) {}
+    fn expect_ships(&mut self, _lengths: &[i32]) {}
+    fn opponent_repaired(&mut self) {}
+    fn play_by(&mut self, _rules: Rules) {}
+}
+
+///
+/// How strongly a player's past ship placements bias hunting.
+/// A point that had a ship every game is weighted 1 + HABIT_WEIGHT times a point that never did.
+///
+pub const HABIT_WEIGHT: f32 = 2.0;
+
+///
+/// The error hunting returns once every point has been guessed
+///
+pub(crate) const NO_POINTS_LEFT: &str = "There are no points left to guess";
+
 ///
 /// This holds all of the information that the computer needs to make
 /// logical guesses
@@ -13,9 +75,18 @@ use std::char;
 /// than the first one.
 /// Num_hits keeps track of how many unresolved hits there are - hits not belonging to a sunk ship.
 /// Last_hit keeps track of the previous hit
-/// guess_version is a random bool to assist in the smart_random_guessing
-/// difficult is a bool: true for difficult, false for easy.
+/// remaining_ships holds the lengths of the enemy ships that have not been sunk yet,
+/// used to pick the parity spacing for smart_random_guessing
+/// settings is the strategy configuration for the chosen difficulty level
 /// guess_count keeps track of how many guesses have been made
+/// heatmap is where the player has put ships in past games, if it is known
+/// misses holds the guesses that missed, which may need guessing again if the player
+/// moves a ship
+/// hits holds every hit that might not belong to a sunk ship, which may have been repaired
+/// recheck holds the hits to guess again, after the player repaired one of them
+/// repairs is how many repaired hits haven't been found again yet
+/// shaped is true if the player's ships are shaped like SHAPED_FLEET
+/// topology is the board being guessed at
 ///
 pub struct PossibleGuesses {
     pub points: Vec<(char, i32)>,
@@ -23,9 +94,16 @@ pub struct PossibleGuesses {
     pub next_guesses: Vec<(char, i32)>,
     pub num_hits: i32,
     pub last_hit: (char, i32),
-    pub guess_version: bool,
-    pub difficult: bool,
+    pub remaining_ships: Vec<i32>,
+    pub settings: AiSettings,
     pub guess_count: i32,
+    pub heatmap: Option<Vec<Vec<f32>>>,
+    pub misses: Vec<(char, i32)>,
+    pub hits: Vec<(char, i32)>,
+    pub recheck: Vec<(char, i32)>,
+    pub repairs: i32,
+    pub shaped: bool,
+    pub topology: Topology,
 }
 
 impl PossibleGuesses {
@@ -35,9 +113,12 @@ impl PossibleGuesses {
     /// sets reasonable_guesses and next_guesses as empty vecs
     /// sets num_hits to 0
     /// sets last_hit to a point outside of the gameboard, to be replaced on the first hit
-    /// sets guess_version to a random bool
-    /// sets difficult as easy
+    /// sets remaining_ships to the lengths of the full fleet
+    /// sets settings to the default difficulty level
     /// sets guess_count at 0
+    /// sets heatmap as unknown
+    /// sets misses, hits and recheck as empty vecs, with no repairs
+    /// sets shaped as false, for straight ships on a square board
     ///
     pub fn new() -> Self {
         let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
@@ -57,85 +138,188 @@ impl PossibleGuesses {
             next_guesses: vec![],
             num_hits: 0,
             last_hit: ('z', 100),
-            guess_version: rand::random(),
-            difficult: false,
+            remaining_ships: FLEET.iter().map(|&(_, length)| length as i32).collect(),
+            settings: Difficulty::default().settings(),
             guess_count: 0,
+            heatmap: None,
+            misses: vec![],
+            hits: vec![],
+            recheck: vec![],
+            repairs: 0,
+            shaped: false,
+            topology: Topology::Square,
         }
     }
 
+    ///
+    /// Creates a new PossibleGuesses struct that plays at the given difficulty level
+    ///
+    pub fn with_difficulty(level: Difficulty) -> Self {
+        let mut possible_guesses = Self::new();
+        possible_guesses.settings = level.settings();
+        possible_guesses
+    }
+
     ///
     /// Returns (col: char, row: i32) to be used in a guess
     /// increments guess_count
-    /// Checks if difficult is set and guess_count is less than 50 (to prevent hanging)
-    ///     if it is, calls smart_random guess,
+    /// Checks the settings, as long as guess_count is under the hunt_cutoff
+    ///     if density is set, calls density_guess,
+    ///     if parity is set, calls smart_random guess,
     ///     otherwise just returns a random guess and removes the guess from points
+    /// Returns an error if every point has already been guessed
     ///
-    pub fn random_guess(&mut self) -> (char, i32) {
+    pub fn random_guess(&mut self) -> Result<(char, i32), String> {
         self.guess_count += 1;
-        if self.difficult && self.guess_count < 50 {
+        let hunting = match self.settings.hunt_cutoff {
+            Some(cutoff) => self.guess_count < cutoff,
+            None => true,
+        };
+        if hunting && self.settings.density {
+            self.density_guess()
+        } else if hunting && self.settings.parity {
             self.smart_random_guess()
+        } else if self.points.is_empty() {
+            Err(NO_POINTS_LEFT.to_string())
         } else {
             let index = (random::<f32>() * self.points.len() as f32).floor() as usize;
-            self.points.remove(index)
+            Ok(self.points.remove(index))
         }
     }
 
     ///
-    /// Creates a vector of 100 points, represent rows and columns
-    /// If guess_version is true, will only provide guesses where the row and column
-    ///     added together are an even number (making a checkerboard pattern)
-    /// Opposite if guess_version is false
-    /// This allows for better guessing
-    /// Then checks the given random guess against the avaiable guessing points (points) to
-    /// make sure it is a valid guess.
-    /// Loops until a valid guess is found
+    /// Returns the parity spacing to hunt with: the smallest Shape::parity of the ships
+    /// that have not been sunk yet, which for straight ships is the length of the shortest.
+    /// Every one of those ships has to cover one cell of each (column + row) % spacing class.
     ///
-    pub fn smart_random_guess(&mut self) -> (char, i32) {
-        let mut reference_points: Vec<(i32, i32)> = vec![];
-        let mut point = ('z', 0);
+    pub fn parity(&self) -> i32 {
+        shapes_for(&self.remaining_ships, self.shaped)
+            .iter()
+            .map(|shape| shape.parity(self.topology))
+            .min()
+            .unwrap_or(1)
+            .max(1)
+    }
 
-        for i in 0..10 {
-            for j in 1..11 {
-                reference_points.push((i, j))
-            }
+    ///
+    /// Splits the remaining points into parity classes, where a point is in the
+    /// Topology::class of its row and column index for the parity.
+    /// Returns the indexes into points of the non-empty class with the fewest points
+    /// left (ties are random), since that class takes the fewest shots to cover.
+    /// If the parity is 1 this is every point.
+    /// Returns an error if there are no points left to guess
+    ///
+    fn parity_class(&self) -> Result<Vec<usize>, String> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let parity = self.parity();
+
+        let mut classes: Vec<Vec<usize>> = vec![vec![]; parity as usize];
+        for (i, (col, row)) in self.points.iter().enumerate() {
+            let col_idx = columns.iter().position(|c| c == col).unwrap() as i32;
+            classes[self.topology.class(*row, col_idx, parity)].push(i);
         }
 
-        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let fewest = classes
+            .iter()
+            .filter(|class| !class.is_empty())
+            .map(|class| class.len())
+            .min()
+            .unwrap_or(0);
+        let mut best: Vec<Vec<usize>> = classes
+            .into_iter()
+            .filter(|class| !class.is_empty() && class.len() == fewest)
+            .collect();
 
-        loop {
-            let (col_attempt, row_attempt) = reference_points
-                [(random::<f32>() * reference_points.len() as f32).floor() as usize];
-            if self.guess_version
-                && (col_attempt + row_attempt) % 2 == 0
-                && self
-                    .points
-                    .contains(&(columns[col_attempt as usize], row_attempt))
-            {
-                if let Some(pos) = self
-                    .points
-                    .iter()
-                    .position(|x| x == &(columns[col_attempt as usize], row_attempt))
-                {
-                    point = self.points.remove(pos);
-                }
-                break;
-            } else if !self.guess_version
-                && (col_attempt + row_attempt) % 2 == 1
-                && self
-                    .points
-                    .contains(&(columns[col_attempt as usize], row_attempt))
-            {
-                if let Some(pos) = self
-                    .points
-                    .iter()
-                    .position(|x| x == &(columns[col_attempt as usize], row_attempt))
-                {
-                    point = self.points.remove(pos);
+        if best.is_empty() {
+            return Err(NO_POINTS_LEFT.to_string());
+        }
+        Ok(best.remove((random::<f32>() * best.len() as f32).floor() as usize))
+    }
+
+    ///
+    /// Picks a random point from the parity class chosen by parity_class
+    /// Removes the guess from points
+    /// Returns an error if there are no points left to guess
+    ///
+    pub fn smart_random_guess(&mut self) -> Result<(char, i32), String> {
+        let class = self.parity_class()?;
+        let index = class[(random::<f32>() * class.len() as f32).floor() as usize];
+        Ok(self.points.remove(index))
+    }
+
+    ///
+    /// Returns how many placements of the remaining ships cover each point in points,
+    /// in the same order as points.
+    /// A placement is the ship's shape, any way it can lie, over points that have all
+    /// not been guessed yet. For straight ships that is a line across or down.
+    ///
+    pub fn density(&self) -> Vec<i32> {
+        let mut counts = vec![0; self.points.len()];
+        let mut index: Vec<Vec<Option<usize>>> = vec![vec![None; 10]; 10];
+        for (i, &(col, row)) in self.points.iter().enumerate() {
+            let (r, c) = get_coord_index(row, col);
+            index[r][c] = Some(i);
+        }
+        let free: Vec<Vec<bool>> = index
+            .iter()
+            .map(|row| row.iter().map(Option::is_some).collect())
+            .collect();
+
+        for shape in shapes_for(&self.remaining_ships, self.shaped) {
+            for placement in shape.placements(&free, self.topology) {
+                for (r, c) in placement {
+                    if let Some(pos) = index[r][c] {
+                        counts[pos] += 1;
+                    }
                 }
-                break;
             }
         }
-        point
+        counts
+    }
+
+    ///
+    /// Returns how much the player's past placements weight a point, from the heatmap.
+    /// 1 if there is no heatmap.
+    ///
+    fn habit_weight(&self, point: (char, i32)) -> f32 {
+        match &self.heatmap {
+            Some(heatmap) => {
+                let (row, col) = get_coord_index(point.1, point.0);
+                1. + HABIT_WEIGHT * heatmap[row][col]
+            }
+            None => 1.,
+        }
+    }
+
+    ///
+    /// Guesses the point with the highest density (ties are random).
+    /// If parity is set only points in the chosen parity class are considered.
+    /// If there is a heatmap, each density is weighted by habit_weight.
+    /// Removes the guess from points
+    /// Returns an error if there are no points left to guess
+    ///
+    pub fn density_guess(&mut self) -> Result<(char, i32), String> {
+        if self.points.is_empty() {
+            return Err(NO_POINTS_LEFT.to_string());
+        }
+        let candidates: Vec<usize> = if self.settings.parity {
+            self.parity_class()?
+        } else {
+            (0..self.points.len()).collect()
+        };
+        let counts = self.density();
+        let scores: Vec<f32> = (0..self.points.len())
+            .map(|i| counts[i] as f32 * self.habit_weight(self.points[i]))
+            .collect();
+
+        let highest = candidates.iter().map(|&i| scores[i]).fold(0., f32::max);
+        let best: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&i| scores[i] == highest)
+            .collect();
+
+        let index = best[(random::<f32>() * best.len() as f32).floor() as usize];
+        Ok(self.points.remove(index))
     }
 
     ///
@@ -176,12 +360,15 @@ impl PossibleGuesses {
     ///     guess_row: i32 - 1-10 the row of the guess
     ///
     /// If report.0 is h, this will check for a sunk ship
-    ///     If it's sunk, it will compare the length of the sunk ship to self.num_hits
+    ///     If it's sunk, it will remove the ship's length from self.remaining_ships, then
+    ///     compare the length of the sunk ship to self.num_hits
     ///         If they are equal it will clear out self.next_guesses and self.reasonable_guess
     ///         Otherwise, it will append everything from self.next_guesses into self.reasonable_guesses
     ///     If the ship is not sunk, it will add appropriate points that are touching the guess into
     ///         self.reasonable guesses, and move less likely guesses into next_guesses based on
-    ///         if this is the first hit or not.
+    ///         if this is the first hit or not. Which points touch, and which lines a ship can
+    ///         lie along, come from the board's topology.
+    /// If targeting is turned off in the settings, only remaining_ships and last_hit are updated
     ///     
     ///
     pub fn update_guesses(&mut self, report: (char, (char, i32)), guess_col: char, guess_row: i32) {
@@ -191,7 +378,12 @@ impl PossibleGuesses {
         // report.0 - h or m for hit or miss
         // report.1.0 - n or s for not sunk or sunk
         // report.1.1 - length of sunk ship
-        if hit == 'h' {
+        if hit == 'h' && sunk == 's' {
+            if let Some(pos) = self.remaining_ships.iter().position(|&l| l == length) {
+                self.remaining_ships.remove(pos);
+            }
+        }
+        if hit == 'h' && self.settings.targeting {
             if sunk == 's' {
                 if length < self.num_hits {
                     self.num_hits -= report.1 .1;
@@ -204,29 +396,12 @@ impl PossibleGuesses {
                     self.next_guesses = vec![];
                 }
             } else {
+                let topology = self.topology;
+                let (row_idx, col_idx) = get_coord_index(guess_row, guess_col);
                 if self.num_hits == 0 {
                     self.num_hits = 1;
-                    let col_idx = columns.iter().position(|&r| r == guess_col).unwrap();
-                    if col_idx < 9 {
-                        let new_point = (columns[col_idx + 1], guess_row);
-                        if self.points.contains(&new_point) {
-                            self.reasonable_guesses.push(new_point);
-                        }
-                    }
-                    if col_idx > 0 {
-                        let new_point = (columns[col_idx - 1], guess_row);
-                        if self.points.contains(&new_point) {
-                            self.reasonable_guesses.push(new_point);
-                        }
-                    }
-                    if guess_row < 10 {
-                        let new_point = (guess_col, guess_row + 1);
-                        if self.points.contains(&new_point) {
-                            self.reasonable_guesses.push(new_point);
-                        }
-                    }
-                    if guess_row > 1 {
-                        let new_point = (guess_col, guess_row - 1);
+                    for (r, c) in topology.neighbors((row_idx, col_idx)) {
+                        let new_point = (columns[c], r as i32 + 1);
                         if self.points.contains(&new_point) {
                             self.reasonable_guesses.push(new_point);
                         }
@@ -234,62 +409,50 @@ impl PossibleGuesses {
                 } else {
                     self.num_hits += 1;
 
-                    let (last_col, last_row) = self.last_hit;
-                    if last_col == guess_col {
-                        if last_row < guess_row && guess_row < 10 {
-                            let new_point = (guess_col, guess_row + 1);
-                            if self.points.contains(&new_point) {
-                                self.reasonable_guesses.push(new_point);
-                            }
-                        } else if last_row > guess_row && guess_row > 1 {
-                            let new_point = (guess_col, guess_row - 1);
-                            if self.points.contains(&new_point) {
-                                self.reasonable_guesses.push(new_point);
-                            }
-                        }
-                        let unlikely_guesses: Vec<(char, i32)> = self
-                            .reasonable_guesses
-                            .iter()
-                            .copied()
-                            .filter(|&p| p.0 != last_col)
-                            .collect();
-                        for coord in unlikely_guesses {
-                            self.next_guesses.push(coord);
-                            if let Some(pos) =
-                                self.reasonable_guesses.iter().position(|x| *x == coord)
-                            {
-                                self.reasonable_guesses.remove(pos);
-                            }
-                        }
+                    // the axis from the last hit to this one, or across if they aren't on one
+                    let (last_row, last_col) = get_coord_index(self.last_hit.1, self.last_hit.0);
+                    let (last_row, last_col) = (last_row as i32, last_col as i32);
+                    let along = |(a_row, a_col): (i32, i32), (d_row, d_col): (i32, i32)| {
+                        d_row * a_col == d_col * a_row
+                    };
+                    let (d_row, d_col) = (row_idx as i32 - last_row, col_idx as i32 - last_col);
+                    let axis = topology
+                        .axes()
+                        .into_iter()
+                        .find(|&axis| along(axis, (d_row, d_col)))
+                        .unwrap_or((0, 1));
+
+                    // carry on past this hit, away from the last one
+                    let step = if axis.0 != 0 {
+                        (d_row * axis.0).signum()
                     } else {
-                        // else if last_row == guess_row
-                        let col_idx = columns.iter().position(|&r| r == guess_col).unwrap();
-                        let last_col_idx = columns.iter().position(|&r| r == last_col).unwrap();
-                        if last_col_idx < col_idx && col_idx < 9 {
-                            let new_point = (columns[col_idx + 1], guess_row);
-                            if self.points.contains(&new_point) {
-                                self.reasonable_guesses.push(new_point);
-                            }
-                        }
-                        if last_col_idx > col_idx && col_idx > 0 {
-                            let new_point = (columns[col_idx - 1], guess_row);
-                            if self.points.contains(&new_point) {
-                                self.reasonable_guesses.push(new_point);
-                            }
+                        (d_col * axis.1).signum()
+                    };
+                    let (r, c) = (
+                        row_idx as i32 + axis.0 * step,
+                        col_idx as i32 + axis.1 * step,
+                    );
+                    if step != 0 && (0..10).contains(&r) && (0..10).contains(&c) {
+                        let new_point = (columns[c as usize], r + 1);
+                        if self.points.contains(&new_point) {
+                            self.reasonable_guesses.push(new_point);
                         }
-                        let unlikely_guesses: Vec<(char, i32)> = self
-                            .reasonable_guesses
-                            .iter()
-                            .copied()
-                            .filter(|&p| p.1 != last_row)
-                            .collect();
-                        for coord in unlikely_guesses {
-                            self.next_guesses.push(coord);
-                            if let Some(pos) =
-                                self.reasonable_guesses.iter().position(|x| *x == coord)
-                            {
-                                self.reasonable_guesses.remove(pos);
-                            }
+                    }
+
+                    let unlikely_guesses: Vec<(char, i32)> = self
+                        .reasonable_guesses
+                        .iter()
+                        .copied()
+                        .filter(|&(col, row)| {
+                            let (r, c) = get_coord_index(row, col);
+                            !along(axis, (r as i32 - last_row, c as i32 - last_col))
+                        })
+                        .collect();
+                    for coord in unlikely_guesses {
+                        self.next_guesses.push(coord);
+                        if let Some(pos) = self.reasonable_guesses.iter().position(|x| *x == coord)
+                        {
+                            self.reasonable_guesses.remove(pos);
                         }
                     }
                 }
@@ -299,6 +462,206 @@ impl PossibleGuesses {
     }
 }
 
+impl Guesser for PossibleGuesses {
+    ///
+    /// After a repair, the hits in recheck are guessed again first.
+    /// If reasonable_guesses or next_guesses aren't empty, there has been a hit
+    /// recently without sinking a ship, so non_random_guess is called.
+    /// Otherwise random_guess is called, which returns an error once there is nothing left.
+    ///
+    fn next_guess(&mut self) -> Result<(char, i32), String> {
+        if let Some(&point) = self.recheck.first() {
+            self.guess_count += 1;
+            Ok(point)
+        } else if self.reasonable_guesses.is_empty() && self.next_guesses.is_empty() {
+            self.random_guess()
+        } else {
+            Ok(self.non_random_guess())
+        }
+    }
+
+    ///
+    /// Takes the point out of points and the targeting guesses, in case it was hit by
+    /// a special weapon rather than picked by next_guess.
+    /// Only hits change what is guessed next, so calls update_guesses on a hit.
+    /// Misses are kept in misses
+    /// A hit being guessed again after a repair reports that it is still hit if it wasn't
+    /// the one that was repaired, and it stays in hits. Once every repaired hit has been
+    /// found, the rest of recheck is dropped.
+    /// Hits are kept in hits until a ship is sunk that they all have to belong to
+    ///
+    fn record_guess(&mut self, report: (char, (char, i32)), col: char, row: i32) {
+        let (hit, (sunk, length)) = report;
+        let still_hit = sunk == 'a';
+        if let Some(pos) = self.recheck.iter().position(|&point| point == (col, row)) {
+            self.recheck.remove(pos);
+            if hit == 'h' && !still_hit {
+                self.repairs -= 1;
+            }
+            if self.repairs <= 0 || self.recheck.is_empty() {
+                self.repairs = 0;
+                self.recheck.clear();
+            }
+            if hit != 'h' {
+                return;
+            }
+        }
+        if still_hit {
+            if !self.hits.contains(&(col, row)) {
+                self.hits.push((col, row));
+            }
+            self.misses.retain(|&point| point != (col, row));
+            for guesses in [
+                &mut self.points,
+                &mut self.reasonable_guesses,
+                &mut self.next_guesses,
+            ] {
+                guesses.retain(|&point| point != (col, row));
+            }
+            return;
+        }
+        if hit == 'h' {
+            self.hits.retain(|&point| point != (col, row));
+            if sunk == 's' || sunk == 'f' {
+                if self.hits.len() < length as usize {
+                    self.hits.clear();
+                }
+            } else {
+                self.hits.push((col, row));
+            }
+        }
+
+        for guesses in [
+            &mut self.points,
+            &mut self.reasonable_guesses,
+            &mut self.next_guesses,
+        ] {
+            guesses.retain(|&point| point != (col, row));
+        }
+        if report.0 == 'h' {
+            self.update_guesses(report, col, row)
+        } else if !self.misses.contains(&(col, row)) {
+            self.misses.push((col, row));
+        }
+    }
+
+    ///
+    /// Keeps the heatmap, which density_guess weights its choices by
+    ///
+    fn learn_habits(&mut self, heatmap: Vec<Vec<f32>>) {
+        self.heatmap = Some(heatmap);
+    }
+
+    ///
+    /// Puts the point back into points
+    ///
+    fn return_guess(&mut self, col: char, row: i32) {
+        if !self.points.contains(&(col, row)) && !self.recheck.contains(&(col, row)) {
+            self.points.push((col, row));
+        }
+    }
+
+    ///
+    /// Only scans while hunting, with no hits to follow up, and only if targeting is
+    /// turned on in the settings. Aims where the scan covers the most unguessed points.
+    ///
+    fn scan_target(&mut self) -> Option<(char, i32)> {
+        if !self.settings.targeting
+            || !self.reasonable_guesses.is_empty()
+            || !self.next_guesses.is_empty()
+        {
+            return None;
+        }
+        best_scan_center(&self.points)
+    }
+
+    ///
+    /// A ship that moved one point can only have moved onto a miss next to a point that
+    /// hasn't been guessed, since ships that have been hit can't move. Those misses are
+    /// put back into points to be guessed again.
+    /// Targeting guesses are kept, since they follow ships that have been hit.
+    ///
+    fn opponent_moved(&mut self) {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let stale: Vec<(char, i32)> = self
+            .misses
+            .iter()
+            .copied()
+            .filter(|&(col, row)| {
+                self.topology
+                    .neighbors(get_coord_index(row, col))
+                    .iter()
+                    .any(|&(r, c)| self.points.contains(&(columns[c], r as i32 + 1)))
+            })
+            .collect();
+        self.misses.retain(|point| !stale.contains(point));
+        self.points.extend(stale);
+    }
+
+    ///
+    /// The repaired hit is one of hits, so they are all put in recheck to be guessed
+    /// again. It no longer counts as an unresolved hit until it is found.
+    ///
+    fn opponent_repaired(&mut self) {
+        self.num_hits = (self.num_hits - 1).max(0);
+        self.repairs += 1;
+        for &point in &self.hits {
+            if !self.recheck.contains(&point) {
+                self.recheck.push(point);
+            }
+        }
+    }
+
+    ///
+    /// Hunts for only these ships, so the parity spacing fits them
+    ///
+    fn expect_ships(&mut self, lengths: &[i32]) {
+        self.remaining_ships = lengths.to_vec();
+    }
+
+    ///
+    /// Hunts for the shapes the rules give the player's ships, on the rules' board
+    ///
+    fn play_by(&mut self, rules: Rules) {
+        self.shaped = rules.shaped;
+        self.topology = rules.topology;
+    }
+
+    ///
+    /// Adds the point to next_guesses, if it hasn't been guessed, so it is guessed before
+    /// hunting anywhere else
+    ///
+    fn reveal(&mut self, col: char, row: i32) {
+        if self.points.contains(&(col, row)) && !self.next_guesses.contains(&(col, row)) {
+            self.next_guesses.push((col, row));
+        }
+    }
+
+    ///
+    /// If the scan found no ships, none of its points are worth guessing, so they are
+    /// taken out of points.
+    /// Otherwise, if targeting is turned on, its unguessed points are added to
+    /// next_guesses, so they are guessed before hunting anywhere else
+    ///
+    fn record_scan(&mut self, area: &[(char, i32)], count: usize) {
+        if count == 0 {
+            for guesses in [
+                &mut self.points,
+                &mut self.reasonable_guesses,
+                &mut self.next_guesses,
+            ] {
+                guesses.retain(|point| !area.contains(point));
+            }
+        } else if self.settings.targeting {
+            for point in area {
+                if self.points.contains(point) && !self.next_guesses.contains(point) {
+                    self.next_guesses.push(*point);
+                }
+            }
+        }
+    }
+}
+
 impl Default for PossibleGuesses {
     fn default() -> Self {
         Self::new()
@@ -317,7 +680,9 @@ mod tests {
         assert!(pg.next_guesses.is_empty());
         assert_eq!(pg.num_hits, 0);
         assert_eq!(pg.last_hit, ('z', 100));
-        assert!(!pg.difficult);
+        assert_eq!(pg.remaining_ships, vec![5, 4, 3, 3, 2]);
+        assert_eq!(pg.parity(), 2);
+        assert_eq!(pg.settings, Difficulty::Easy.settings());
         assert_eq!(pg.guess_count, 0);
     }
 
@@ -362,5 +727,168 @@ mod tests {
         assert_eq!(pg.num_hits, 0);
         assert!(pg.next_guesses.is_empty());
         assert!(pg.reasonable_guesses.is_empty());
+        assert_eq!(pg.remaining_ships, vec![5, 4, 3, 2]);
+    }
+
+    #[test]
+    fn test_update_guesses_hex() {
+        let mut pg = PossibleGuesses::new();
+        pg.topology = Topology::Hex;
+        pg.update_guesses(('h', ('n', 0)), 'C', 3);
+        assert_eq!(pg.reasonable_guesses.len(), 6);
+        assert!(pg.reasonable_guesses.contains(&('B', 4)));
+        assert!(pg.reasonable_guesses.contains(&('D', 2)));
+
+        // a second hit down and to the left follows that axis
+        pg.points.retain(|&p| p != ('B', 4));
+        pg.update_guesses(('h', ('n', 0)), 'B', 4);
+        assert!(pg.reasonable_guesses.contains(&('A', 5)));
+        assert!(pg.reasonable_guesses.contains(&('D', 2)));
+        assert!(pg.next_guesses.contains(&('C', 4)));
+        assert!(!pg.reasonable_guesses.contains(&('C', 2)));
+
+        // a hex line of 2 doesn't cover 2 classes, so every point is hunted
+        pg.remaining_ships = vec![5, 2];
+        assert_eq!(pg.parity(), 1);
+        pg.remaining_ships = vec![5, 4];
+        assert_eq!(pg.parity(), 3);
+    }
+
+    #[test]
+    fn test_smart_random_guess_parity() {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let mut pg = PossibleGuesses::new();
+        pg.settings = Difficulty::Medium.settings();
+        pg.remaining_ships = vec![5, 3];
+        assert_eq!(pg.parity(), 3);
+
+        let mut classes = vec![];
+        for _ in 0..20 {
+            let (col, row) = pg.random_guess().unwrap();
+            let col_idx = columns.iter().position(|&c| c == col).unwrap() as i32;
+            classes.push((col_idx + row) % 3);
+        }
+        assert!(classes.iter().all(|&c| c == classes[0]));
+        assert_eq!(pg.points.len(), 80);
+
+        // once every point has been guessed it keeps working through what is left
+        for _ in 0..80 {
+            pg.random_guess().unwrap();
+        }
+        assert!(pg.points.is_empty());
+        assert!(pg.smart_random_guess().is_err());
+        assert!(pg.density_guess().is_err());
+        assert!(pg.random_guess().is_err());
+    }
+
+    #[test]
+    fn test_density_guess() {
+        let mut pg = PossibleGuesses::with_difficulty(Difficulty::Hard);
+        pg.settings.parity = false;
+        pg.remaining_ships = vec![5];
+        let counts = pg.density();
+        assert_eq!(counts.iter().max(), Some(&10));
+
+        // the only points a length 5 ship covers 10 ways are in the middle of the board
+        let guess = pg.random_guess().unwrap();
+        assert!(['E', 'F'].contains(&guess.0));
+        assert!([5, 6].contains(&guess.1));
+        assert_eq!(pg.points.len(), 99);
+    }
+
+    #[test]
+    fn test_no_targeting() {
+        let mut pg = PossibleGuesses::with_difficulty(Difficulty::Beginner);
+        pg.update_guesses(('h', ('u', 0)), 'B', 3);
+        assert!(pg.reasonable_guesses.is_empty());
+        assert_eq!(pg.num_hits, 0);
+        pg.update_guesses(('h', ('s', 2)), 'B', 4);
+        assert_eq!(pg.remaining_ships, vec![5, 4, 3, 3]);
+    }
+
+    #[test]
+    fn test_learn_habits() {
+        let mut pg = PossibleGuesses::with_difficulty(Difficulty::Hard);
+        pg.settings.parity = false;
+        pg.remaining_ships = vec![2];
+
+        // this player always puts a ship in the corner
+        let mut heatmap = vec![vec![0.; 10]; 10];
+        heatmap[0][0] = 1.;
+        pg.learn_habits(heatmap);
+        assert_eq!(pg.random_guess(), Ok(('A', 1)));
+    }
+
+    #[test]
+    fn test_opponent_moved() {
+        let mut pg = PossibleGuesses::new();
+        pg.points.retain(|&(col, _)| col != 'A' && col != 'B');
+        for row in 1..=10 {
+            pg.record_guess(('m', ('n', 0)), 'A', row);
+            pg.record_guess(('m', ('n', 0)), 'B', row);
+        }
+        pg.opponent_moved();
+        assert_eq!(pg.points.len(), 90);
+        assert!(pg.points.contains(&('B', 4)));
+        assert!(!pg.points.contains(&('A', 4)));
+        assert_eq!(pg.misses.len(), 10);
+
+        // on a hex board C5 touches D4, down and to the left of it
+        let mut pg = PossibleGuesses::new();
+        pg.points = vec![('C', 5)];
+        pg.misses = vec![('D', 4)];
+        pg.opponent_moved();
+        assert_eq!(pg.misses.len(), 1);
+        pg.topology = Topology::Hex;
+        pg.opponent_moved();
+        assert!(pg.misses.is_empty());
+    }
+
+    #[test]
+    fn test_record_scan() {
+        let mut pg = PossibleGuesses::new();
+        assert!(pg.scan_target().is_some());
+        pg.record_scan(&[('A', 1), ('A', 2), ('B', 1), ('B', 2)], 0);
+        assert_eq!(pg.points.len(), 96);
+        assert!(!pg.points.contains(&('B', 2)));
+
+        pg.record_scan(&[('E', 5), ('E', 6), ('A', 1)], 2);
+        assert_eq!(pg.next_guesses, vec![('E', 5), ('E', 6)]);
+        assert!(pg.scan_target().is_none());
+        assert!([('E', 5), ('E', 6)].contains(&pg.next_guess().unwrap()));
+    }
+
+    #[test]
+    fn test_reveal() {
+        let mut pg = PossibleGuesses::new();
+        pg.reveal('C', 7);
+        pg.reveal('C', 7);
+        assert_eq!(pg.next_guesses, vec![('C', 7)]);
+        assert_eq!(pg.next_guess(), Ok(('C', 7)));
+        assert!(!pg.points.contains(&('C', 7)));
+    }
+
+    #[test]
+    fn test_opponent_repaired() {
+        let mut pg = PossibleGuesses::new();
+        pg.record_guess(('h', ('n', 0)), 'A', 1);
+        pg.record_guess(('h', ('s', 2)), 'A', 2);
+        pg.record_guess(('h', ('n', 0)), 'D', 4);
+        pg.record_guess(('h', ('n', 0)), 'D', 5);
+        assert_eq!(pg.hits, vec![('D', 4), ('D', 5)]);
+
+        // D5 was repaired: D4 is still a hit, and stays one
+        pg.opponent_repaired();
+        assert_eq!(pg.num_hits, 1);
+        assert_eq!(pg.next_guess(), Ok(('D', 4)));
+        pg.record_guess(('h', ('a', 0)), 'D', 4);
+        assert!(!pg.misses.contains(&('D', 4)));
+        assert!(pg.hits.contains(&('D', 4)));
+        assert_eq!(pg.repairs, 1);
+        assert_eq!(pg.next_guess(), Ok(('D', 5)));
+        pg.record_guess(('h', ('n', 0)), 'D', 5);
+        assert!(pg.recheck.is_empty());
+        assert_eq!(pg.num_hits, 2);
+        assert_eq!(pg.hits, vec![('D', 4), ('D', 5)]);
     }
 }
@@ -0,0 +1,176 @@
+use crate::layers::Layer;
+use crate::ship::Fleet;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+///
+/// The folder everything the game remembers between runs is stored in.
+/// Uses $BATTLESHIP_HOME if it is set, otherwise ~/.battleship,
+/// otherwise the current directory.
+///
+pub fn data_dir() -> PathBuf {
+    if let Ok(home) = env::var("BATTLESHIP_HOME") {
+        return PathBuf::from(home);
+    }
+    match env::var("HOME") {
+        Ok(home) => Path::new(&home).join(".battleship"),
+        Err(_) => PathBuf::from("."),
+    }
+}
+
+///
+/// Where one player has put their ships over every game they have finished.
+/// name is the player's name
+/// games is how many games have been recorded
+/// placements counts, for each (row, column), how many games had a ship on it
+/// Saved as JSON.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub games: i32,
+    pub placements: Vec<Vec<i32>>,
+}
+
+impl PlayerProfile {
+    ///
+    /// Creates an empty profile for the named player
+    ///
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            games: 0,
+            placements: vec![vec![0; 10]; 10],
+        }
+    }
+
+    ///
+    /// The folder profiles are stored in, a profiles folder in data_dir()
+    ///
+    pub fn default_dir() -> PathBuf {
+        data_dir().join("profiles")
+    }
+
+    ///
+    /// Returns the file a player's profile is saved in.
+    /// Lowercase letters and numbers from the name are kept, and every other character
+    /// is written as _ and its hex code then _, so Sam O'Neil is _53_am_20__4f__27__4e_eil.
+    /// That way no two names share a file, whatever the folder's case rules, and the
+    /// name can't point outside of dir.
+    ///
+    fn path(dir: &Path, name: &str) -> PathBuf {
+        let file: String = name
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | '0'..='9' => c.to_string(),
+                _ => format!("_{:x}_", c as u32),
+            })
+            .collect();
+        dir.join(format!("{file}.json"))
+    }
+
+    ///
+    /// Arguements:
+    ///     dir: &Path - the folder profiles are stored in
+    ///     name: &str - the player's name
+    /// Returns the saved profile for the player, or a new empty profile if there
+    /// isn't one or it can't be read.
+    ///
+    pub fn load(dir: &Path, name: &str) -> Self {
+        let saved = fs::read_to_string(PlayerProfile::path(dir, name))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<PlayerProfile>(&contents).ok())
+            .filter(|saved| {
+                saved.placements.len() == 10 && saved.placements.iter().all(|row| row.len() == 10)
+            });
+        match saved {
+            Some(saved) => Self {
+                name: name.to_string(),
+                ..saved
+            },
+            None => PlayerProfile::new(name),
+        }
+    }
+
+    ///
+    /// Saves the profile into dir, creating the folder if needed
+    ///
+    pub fn save(&self, dir: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        fs::write(PlayerProfile::path(dir, &self.name), contents)
+    }
+
+    ///
+    /// Arguements:
+    ///     fleet: &Fleet - the player's ships, as in Grid::fleet, where they were placed
+    /// Adds one game to the profile, counting every point a ship was on. Only the surface
+    /// counts, since that is the only layer the heatmap is used to hunt on.
+    ///
+    pub fn record(&mut self, fleet: &Fleet) {
+        self.games += 1;
+        for ship in &fleet.on_layer(Layer::Surface).ships {
+            for &(row, col) in &ship.cells {
+                if row < 10 && col < 10 {
+                    self.placements[row][col] += 1;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Returns, for each (row, column), the share of recorded games that had a
+    /// ship on it, from 0 to 1.
+    ///
+    pub fn heatmap(&self) -> Vec<Vec<f32>> {
+        self.placements
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&count| count as f32 / self.games.max(1) as f32)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_save_load() {
+        let dir = env::temp_dir().join(format!("battleship-profile-{}", std::process::id()));
+        let mut fleet = Fleet::new();
+        fleet.add("Destroyer", vec![(0, 0), (0, 1)]);
+
+        let mut profile = PlayerProfile::load(&dir, "Sam O'Neil");
+        assert_eq!(profile.games, 0);
+        profile.record(&fleet);
+        profile.record(&fleet);
+        fleet.remove("Destroyer");
+        fleet.add("Destroyer", vec![(0, 0), (1, 0)]);
+        fleet.add_on("Submarine", vec![(5, 5), (5, 6), (5, 7)], Layer::Deep);
+        profile.record(&fleet);
+        profile.save(&dir).unwrap();
+
+        let loaded = PlayerProfile::load(&dir, "Sam O'Neil");
+        assert_eq!(loaded, profile);
+        assert_eq!(loaded.games, 3);
+        let heatmap = loaded.heatmap();
+        assert_eq!(heatmap[0][0], 1.);
+        assert!((heatmap[0][1] - 2. / 3.).abs() < 0.001);
+        assert_eq!(heatmap[5][5], 0.);
+
+        // names that only differ in punctuation, case or script keep their own files
+        for name in ["samoneil", "Samoneil", "Мария", "李"] {
+            assert_eq!(PlayerProfile::load(&dir, name).games, 0);
+        }
+        assert!(PlayerProfile::path(&dir, "Мария") != PlayerProfile::path(&dir, "李"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use crate::coord::*;
+use crate::grid::Grid;
+use crate::layers::Layer;
+use crate::rng::random;
+
+///
+/// How many hits each player can repair in a game, with the repair rule
+///
+pub const REPAIRS: i32 = 2;
+
+impl Grid {
+    ///
+    /// Repairs a hit on one of this board's ships on the surface, in the repair rules
+    /// Arguements:
+    ///     row: i32 - 1-10 - the row of the hit
+    ///     column: char - A-J - the column of the hit
+    /// Returns the class of the ship that was repaired, or an error saying why it can't
+    /// be: there is no hit ship there, or the ship is already sunk.
+    /// So the other side can't see which point was repaired, every hit on the surface
+    /// that isn't part of a sunk ship is drawn as uncertain, the repaired one too. Firing
+    /// at one of them again shows whether it is still hit, see Grid::guess_layer
+    ///
+    pub(crate) fn repair(&mut self, row: i32, column: char) -> Result<String, String> {
+        let (r, c) = get_coord_index(row, column);
+        let Some(ship) = self
+            .fleet
+            .ship_at_mut(Layer::Surface, (r, c))
+            .filter(|ship| ship.is_hit((r, c)))
+        else {
+            return Err(format!("There is no hit to repair at {column}{row}"));
+        };
+        if ship.is_sunk() {
+            return Err(format!(
+                "The {} is sunk, so it can't be repaired",
+                ship.class
+            ));
+        }
+        ship.repair((r, c));
+        let class = ship.class.clone();
+
+        let plane = Layer::Surface.index();
+        for r in 0..10 {
+            for c in 0..10 {
+                let sunk = self
+                    .fleet
+                    .ship_at(Layer::Surface, (r, c))
+                    .is_some_and(|ship| ship.is_sunk());
+                if self.grid[plane][r][c].state == CoordState::Hit && !sunk {
+                    self.grid[plane][r][c].state = CoordState::Uncertain;
+                }
+            }
+        }
+        self.grid[plane][r][c].state = CoordState::Uncertain;
+        Ok(class)
+    }
+
+    ///
+    /// The computer's repair, in the repair rules
+    /// If one of its ships on the surface is one hit from sinking, one of that ship's hits,
+    /// picked at random, is repaired.
+    /// Returns true if a hit was repaired, which uses up the computer's turn
+    ///
+    pub(crate) fn computer_repair(&mut self) -> bool {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let Some(ship) = self.fleet.ships.iter().find(|ship| {
+            ship.layer == Layer::Surface && ship.hits() > 0 && ship.hits() + 1 == ship.len()
+        }) else {
+            return false;
+        };
+        let hits: Vec<(usize, usize)> = ship
+            .cells
+            .iter()
+            .zip(ship.damage.iter())
+            .filter(|(_, &hit)| hit)
+            .map(|(&cell, _)| cell)
+            .collect();
+        let (r, c) = hits[(random::<f32>() * hits.len() as f32).floor() as usize];
+        if self.repair(r as i32 + 1, columns[c]).is_err() {
+            return false;
+        }
+        if !self.quiet {
+            println!("The computer repaired one of its ships");
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Outcome;
+
+    #[test]
+    fn test_repair() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        let mut points: Vec<(usize, usize)> =
+            (0..10).flat_map(|r| (0..10).map(move |c| (r, c))).collect();
+        grid.place_user_ship("Cruiser".to_string(), (2, 'C'), (4, 'C'), 3, &mut points);
+        grid.place_user_ship("Destroyer".to_string(), (1, 'A'), (1, 'B'), 2, &mut points);
+
+        assert!(grid.repair(2, 'C').is_err());
+        grid.guess(2, 'C');
+        grid.guess(3, 'C');
+        assert_eq!(grid.repair(2, 'C'), Ok("Cruiser".to_string()));
+        // the repaired hit and the one left look the same to the other side
+        assert_eq!(grid.grid[0][1][2].state, CoordState::Uncertain);
+        assert_eq!(grid.grid[0][2][2].state, CoordState::Uncertain);
+        assert_eq!(grid.fleet.get("Cruiser").unwrap().hits(), 1);
+        assert!(grid.repair(2, 'C').is_err());
+        assert!(grid.repair(5, 'E').is_err());
+        assert_eq!(grid.fire(3, 'C', Layer::Surface), Ok(Outcome::StillHit));
+        assert_eq!(grid.grid[0][2][2].state, CoordState::Hit);
+        let cruiser = grid.fleet.get("Cruiser").unwrap().id;
+        let shot = grid.history.last().unwrap();
+        assert_eq!((shot.report, shot.ship), (('h', ('a', 0)), Some(cruiser)));
+        assert_eq!(grid.fleet.get("Cruiser").unwrap().hits(), 1);
+
+        // a ship one hit from sinking is repaired by the computer, a sunk one isn't
+        grid.guess(1, 'A');
+        grid.guess(1, 'B');
+        assert!(!grid.computer_repair());
+        grid.guess(2, 'C');
+        assert!(grid.computer_repair());
+        assert_eq!(grid.fleet.get("Cruiser").unwrap().hits(), 1);
+        assert!(grid.repair(1, 'A').is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use rand::{
+    distributions::{Distribution, Standard},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+use std::cell::RefCell;
+
+thread_local! {
+    static SEEDED: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+///
+/// Arguements:
+///     seed: u64 - the seed for every random choice made on this thread from now on
+/// Two runs with the same seed and the same input play out exactly the same way
+///
+pub fn seed(seed: u64) {
+    SEEDED.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+///
+/// Returns true if seed has been called on this thread
+///
+pub fn is_seeded() -> bool {
+    SEEDED.with(|rng| rng.borrow().is_some())
+}
+
+///
+/// Used in place of rand::random everywhere in the game.
+/// Returns a random value from the seeded generator if seed has been called,
+/// otherwise from rand's thread generator
+///
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    SEEDED.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => rng.gen(),
+        None => rand::random(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        assert!(!is_seeded());
+        seed(7);
+        assert!(is_seeded());
+        let first: Vec<f32> = (0..5).map(|_| random::<f32>()).collect();
+        seed(7);
+        let second: Vec<f32> = (0..5).map(|_| random::<f32>()).collect();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::topology::Topology;
+
+///
+/// The optional rules a game can be played with. All of them are off by default.
+/// advanced: "Advanced Mission" special weapons, see the weapons module
+/// sonar: a sonar scan each game, see the sonar module
+/// scan_every: with sonar, how many turns after using its scan a player gets another one,
+///     or 0 for only one scan a game
+/// mobile: ships that haven't been hit can move instead of firing, see the mobile module
+/// mines: each player places mines and decoys as well as ships, see the mines module
+/// layers: the submarine dives to a deep layer under the board, see the layers module
+/// repair: a player can repair a hit instead of firing, see the repair module
+/// shaped: the carrier, battleship and cruiser are shaped like SHAPED_FLEET, see the
+///     shapes module. It is set with --fleet shapes rather than by name.
+/// topology: the board the game is played on, see the topology module. It is set with
+///     --board rather than by name.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rules {
+    pub advanced: bool,
+    pub sonar: bool,
+    pub scan_every: i32,
+    pub mobile: bool,
+    pub mines: bool,
+    pub layers: bool,
+    pub repair: bool,
+    pub shaped: bool,
+    pub topology: Topology,
+}
+
+impl Rules {
+    ///
+    /// The name of every rule, as used by from_input
+    ///
+    pub const NAMES: [&'static str; 6] =
+        ["advanced", "sonar", "mobile", "mines", "layers", "repair"];
+
+    ///
+    /// Arguements:
+    ///     input: &str - rule names separated by commas, like "advanced,sonar", not case
+    ///         sensitive. sonar=N turns on sonar with a new scan N turns after each one
+    /// Returns the rules with each named rule turned on, or an error naming the first
+    /// rule that doesn't exist
+    ///
+    pub fn from_input(input: &str) -> Result<Rules, String> {
+        let mut rules = Rules::default();
+        for name in input.split(',') {
+            match name.trim().to_lowercase().as_str() {
+                "advanced" => rules.advanced = true,
+                "sonar" => rules.sonar = true,
+                other if other.starts_with("sonar=") => {
+                    match other["sonar=".len()..].trim().parse::<i32>() {
+                        Ok(turns) if turns > 0 => {
+                            rules.sonar = true;
+                            rules.scan_every = turns;
+                        }
+                        _ => return Err(format!("{other} needs a number of turns above 0")),
+                    }
+                }
+                "mobile" => rules.mobile = true,
+                "mines" => rules.mines = true,
+                "layers" => rules.layers = true,
+                "repair" => rules.repair = true,
+                "" => {}
+                other => {
+                    return Err(format!(
+                        "there is no rule called {other}, the rules are: {}",
+                        Rules::NAMES.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_input() {
+        assert_eq!(Rules::from_input(""), Ok(Rules::default()));
+        assert!(Rules::from_input("Advanced").unwrap().advanced);
+        let both = Rules::from_input("advanced, sonar").unwrap();
+        assert!(both.advanced && both.sonar && !both.mobile);
+        assert!(Rules::from_input("advanced,warp").is_err());
+        let every = Rules::from_input("sonar=5").unwrap();
+        assert!(every.sonar && every.scan_every == 5);
+        assert!(Rules::from_input("sonar=0").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::rng;
+use std::{cell::RefCell, collections::VecDeque, fs, path::Path};
+
+thread_local! {
+    static RUNNING: RefCell<Option<VecDeque<String>>> = const { RefCell::new(None) };
+}
+
+///
+/// A whole game read from a file, for playing without a keyboard.
+/// seed is the seed the game is played with
+/// lines are the answers to every prompt, in the order they are asked:
+///     difficulty and placement style (unless given on the command line),
+///     each ship placement, 'y' to confirm the fleet, the guesses, and
+///     finally whether to play again
+///
+/// In the file, blank lines and lines starting with # are skipped,
+/// and a line like "seed 42" sets the seed, which is 0 otherwise
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    pub seed: u64,
+    pub lines: Vec<String>,
+}
+
+impl Script {
+    ///
+    /// Arguements:
+    ///     contents: &str - the text of a script file
+    /// Returns the script, or an error naming the line if a seed can't be read
+    ///
+    pub fn parse(contents: &str) -> Result<Script, String> {
+        let mut script = Script {
+            seed: 0,
+            lines: vec![],
+        };
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.strip_prefix("seed ") {
+                Some(seed) => {
+                    script.seed = seed
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("line {}: the seed must be a number", i + 1))?;
+                }
+                None => script.lines.push(line.to_string()),
+            }
+        }
+        Ok(script)
+    }
+
+    ///
+    /// Reads and parses the script file at path
+    ///
+    pub fn load(path: &Path) -> Result<Script, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("unable to read {}: {e}", path.display()))?;
+        Script::parse(&contents)
+    }
+
+    ///
+    /// Seeds the game with the script's seed and makes read_input_line take its
+    /// input from the script's lines instead of the keyboard
+    ///
+    pub fn start(self) {
+        rng::seed(self.seed);
+        RUNNING.with(|running| *running.borrow_mut() = Some(self.lines.into()));
+    }
+}
+
+///
+/// Returns true if a script has been started
+///
+pub fn is_running() -> bool {
+    RUNNING.with(|running| running.borrow().is_some())
+}
+
+///
+/// Returns the next line of the running script, or None if it has run out.
+/// The line is printed, so the output reads like a transcript of someone typing it.
+///
+pub fn next_line() -> Option<String> {
+    let line = RUNNING.with(|running| running.borrow_mut().as_mut()?.pop_front())?;
+    println!("{line}");
+    Some(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script() {
+        let script = Script::parse("# a quick game\nseed 42\n\neasy\nA1 H\n  y  \n").unwrap();
+        assert_eq!(script.seed, 42);
+        assert_eq!(script.lines, vec!["easy", "A1 H", "y"]);
+        assert!(Script::parse("seed many").is_err());
+
+        assert!(!is_running());
+        assert_eq!(next_line(), None);
+        script.start();
+        assert!(is_running());
+        assert!(rng::is_seeded());
+        assert_eq!(next_line(), Some("easy".to_string()));
+        assert_eq!(next_line(), Some("A1 H".to_string()));
+        assert_eq!(next_line(), Some("y".to_string()));
+        assert_eq!(next_line(), None);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::grid::FLEET;
+use crate::topology::Topology;
+
+///
+/// The pieces ships take in the shaped fleet, by class, as the (row, column) of each
+/// of their points. Ships that aren't listed stay straight.
+///     Carrier: a plus
+///     Battleship: a T
+///     Cruiser: an L
+///
+pub const SHAPED_FLEET: [(&str, &[(i32, i32)]); 3] = [
+    ("Carrier", &[(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]),
+    ("Battleship", &[(0, 0), (0, 1), (0, 2), (1, 1)]),
+    ("Cruiser", &[(0, 0), (1, 0), (1, 1)]),
+];
+
+///
+/// The outline of a ship, a polyomino, as the (row, column) of each of its points
+/// measured from its anchor: the top left corner of the smallest box around it.
+/// The points are kept sorted, so two shapes with the same points are equal.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shape {
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl Shape {
+    ///
+    /// Creates a shape from its points, moving it so its anchor is (0, 0)
+    ///
+    pub fn new(cells: &[(i32, i32)]) -> Self {
+        let min_row = cells.iter().map(|&(r, _)| r).min().unwrap_or(0);
+        let min_col = cells.iter().map(|&(_, c)| c).min().unwrap_or(0);
+        let mut cells: Vec<(i32, i32)> = cells
+            .iter()
+            .map(|&(r, c)| (r - min_row, c - min_col))
+            .collect();
+        cells.sort();
+        Self { cells }
+    }
+
+    ///
+    /// Creates a straight ship of the given length, running down
+    ///
+    pub fn line(length: u32) -> Self {
+        Shape::new(&(0..length as i32).map(|i| (i, 0)).collect::<Vec<_>>())
+    }
+
+    ///
+    /// Returns how many points the shape covers
+    ///
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    ///
+    /// Returns true if the shape covers no points
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    ///
+    /// Returns true if the shape is a straight line
+    ///
+    pub fn is_line(&self) -> bool {
+        self.cells.iter().all(|&(r, _)| r == 0) || self.cells.iter().all(|&(_, c)| c == 0)
+    }
+
+    ///
+    /// Arguements:
+    ///     topology: Topology - the board the shape lies on
+    /// Returns every different way the shape can lie: turned a quarter at a time, and
+    /// flipped over and turned again. Always starts with the shape as it was given.
+    /// A straight ship lies along each of the board's axes instead, down first and
+    /// across second.
+    ///
+    pub fn orientations(&self, topology: Topology) -> Vec<Shape> {
+        let mut orientations: Vec<Shape> = vec![];
+        if self.is_line() {
+            for (d_row, d_col) in topology.axes() {
+                let cells: Vec<(i32, i32)> = (0..self.len() as i32)
+                    .map(|i| (d_row * i, d_col * i))
+                    .collect();
+                let shape = Shape::new(&cells);
+                if !orientations.contains(&shape) {
+                    orientations.push(shape);
+                }
+            }
+            return orientations;
+        }
+        for flipped in [false, true] {
+            let mut cells: Vec<(i32, i32)> = self
+                .cells
+                .iter()
+                .map(|&(r, c)| if flipped { (r, -c) } else { (r, c) })
+                .collect();
+            for _ in 0..4 {
+                let shape = Shape::new(&cells);
+                if !orientations.contains(&shape) {
+                    orientations.push(shape);
+                }
+                cells = cells.iter().map(|&(r, c)| (c, -r)).collect();
+            }
+        }
+        orientations
+    }
+
+    ///
+    /// Arguements:
+    ///     anchor: (usize, usize) - the (row, column) of the shape's anchor
+    /// Returns the (row, column) of every point the shape covers from there, or None if
+    /// any of them are off the board
+    ///
+    pub fn at(&self, anchor: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        let mut points = vec![];
+        for &(r, c) in &self.cells {
+            let (r, c) = (anchor.0 as i32 + r, anchor.1 as i32 + c);
+            if !(0..10).contains(&r) || !(0..10).contains(&c) {
+                return None;
+            }
+            points.push((r as usize, c as usize));
+        }
+        Some(points)
+    }
+
+    ///
+    /// Arguements:
+    ///     free: &[Vec<bool>] - for each (row, column), whether a ship can go there
+    ///     topology: Topology - the board the shape lies on
+    /// Returns every placement of the shape, in any orientation, that only covers free
+    /// points. They go by anchor, a row at a time, then by orientation.
+    ///
+    pub fn placements(&self, free: &[Vec<bool>], topology: Topology) -> Vec<Vec<(usize, usize)>> {
+        let orientations = self.orientations(topology);
+        let mut placements = vec![];
+        for row in 0..10 {
+            for col in 0..10 {
+                for shape in &orientations {
+                    if let Some(points) = shape.at((row, col)) {
+                        if points.iter().all(|&(r, c)| free[r][c]) {
+                            placements.push(points);
+                        }
+                    }
+                }
+            }
+        }
+        placements
+    }
+
+    ///
+    /// Arguements:
+    ///     topology: Topology - the board the shape lies on
+    /// Returns the largest spacing k, up to the shape's length, where the shape covers
+    /// a point of every parity class of k (see Topology::class) however it lies. Hunting
+    /// one class of that spacing is sure to find the ship.
+    ///
+    pub fn parity(&self, topology: Topology) -> i32 {
+        let orientations = self.orientations(topology);
+        (1..=self.len() as i32)
+            .rev()
+            .find(|&k| {
+                orientations.iter().all(|shape| {
+                    (0..k as usize).all(|class| {
+                        shape
+                            .cells
+                            .iter()
+                            .any(|&(r, c)| topology.class(r, c, k) == class)
+                    })
+                })
+            })
+            .unwrap_or(1)
+    }
+
+    ///
+    /// Returns the shape drawn as lines of text, # for its points and . around them
+    ///
+    pub fn draw(&self) -> Vec<String> {
+        let rows = self.cells.iter().map(|&(r, _)| r).max().unwrap_or(0) + 1;
+        let cols = self.cells.iter().map(|&(_, c)| c).max().unwrap_or(0) + 1;
+        (0..rows)
+            .map(|r| {
+                (0..cols)
+                    .map(|c| {
+                        if self.cells.contains(&(r, c)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+///
+/// Arguements:
+///     class: &str - the ship's class, as in FLEET
+///     shaped: bool - true if the game is played with the shaped fleet
+/// Returns the shape of the ship: its piece from SHAPED_FLEET if the fleet is shaped, or
+/// else a straight line as long as it is in FLEET
+///
+pub fn shape_of(class: &str, shaped: bool) -> Shape {
+    if shaped {
+        if let Some((_, cells)) = SHAPED_FLEET.iter().find(|(name, _)| *name == class) {
+            return Shape::new(cells);
+        }
+    }
+    let length = FLEET
+        .iter()
+        .find(|(name, _)| *name == class)
+        .map_or(0, |&(_, length)| length);
+    Shape::line(length)
+}
+
+///
+/// Arguements:
+///     lengths: &[i32] - the lengths of the ships that haven't been sunk
+///     shaped: bool - true if the game is played with the shaped fleet
+/// Returns the shape of each of them. Reports only give the length of a sunk ship, so
+/// when two ships are the same length the one first in FLEET is taken as sunk first.
+///
+pub fn shapes_for(lengths: &[i32], shaped: bool) -> Vec<Shape> {
+    let mut left: Vec<i32> = lengths.to_vec();
+    let mut shapes = vec![];
+    for (name, length) in FLEET.iter().rev() {
+        if let Some(pos) = left.iter().position(|&l| l == *length as i32) {
+            left.remove(pos);
+            shapes.push(shape_of(name, shaped));
+        }
+    }
+    shapes.reverse();
+    shapes.extend(left.iter().map(|&length| Shape::line(length as u32)));
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes() {
+        let plus = Shape::new(SHAPED_FLEET[0].1);
+        let t = Shape::new(SHAPED_FLEET[1].1);
+        let l = Shape::new(SHAPED_FLEET[2].1);
+        let square = Topology::Square;
+        assert_eq!(plus.orientations(square).len(), 1);
+        assert_eq!(t.orientations(square).len(), 4);
+        assert_eq!(l.orientations(square).len(), 4);
+        assert_eq!(Shape::line(3).orientations(square).len(), 2);
+        assert_eq!(Shape::line(3).orientations(Topology::Hex).len(), 3);
+        assert_eq!(
+            Shape::new(&[(0, 0), (1, 0), (2, 0), (2, 1)])
+                .orientations(square)
+                .len(),
+            8
+        );
+
+        assert!(Shape::line(4).is_line());
+        assert!(!t.is_line());
+        assert_eq!(Shape::line(4).parity(square), 4);
+        assert_eq!(t.parity(square), 3);
+        assert_eq!(t.draw(), vec!["###", ".#."]);
+        assert_eq!(t.at((9, 0)), None);
+        assert_eq!(t.at((8, 7)), Some(vec![(8, 7), (8, 8), (8, 9), (9, 8)]));
+
+        let free = vec![vec![true; 10]; 10];
+        assert_eq!(Shape::line(2).placements(&free, square).len(), 180);
+        assert_eq!(plus.placements(&free, square).len(), 64);
+
+        assert_eq!(shape_of("Battleship", false), Shape::line(4));
+        assert_eq!(shape_of("Battleship", true), t);
+        assert_eq!(shapes_for(&[5, 3], true), vec![plus, Shape::line(3)]);
+    }
+}
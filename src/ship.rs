@@ -0,0 +1,321 @@
+use crate::layers::Layer;
+
+///
+/// Which way a ship lies on the board
+///     Horizontal: along a row
+///     Vertical: down a column
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+///
+/// Which ship in a fleet a ship is. Ids are given out in the order ships are added and
+/// are never reused, so a ship keeps its id when it moves or another ship is taken out.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShipId(pub usize);
+
+///
+/// One ship, for the whole game
+/// id: which ship in the fleet it is, which never changes
+/// class: what kind of ship it is, like Carrier
+/// cells: the (row, column) of every point it covers, in order from one end
+/// orientation: which way it lies
+/// damage: for each of cells, whether that point has been hit
+/// layer: the layer of the board it is on, which is the surface without the layers rule
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ship {
+    pub id: ShipId,
+    pub class: String,
+    pub cells: Vec<(usize, usize)>,
+    pub orientation: Orientation,
+    pub damage: Vec<bool>,
+    pub layer: Layer,
+}
+
+impl Ship {
+    ///
+    /// Creates an undamaged ship
+    /// Arguements:
+    ///     id: ShipId - which ship in the fleet it is
+    ///     class: &str - what kind of ship it is
+    ///     cells: Vec<(usize, usize)> - the points it covers. The orientation is
+    ///         Vertical if the first two are in the same column, otherwise Horizontal.
+    /// The ship is on the surface
+    ///
+    pub fn new(id: ShipId, class: &str, cells: Vec<(usize, usize)>) -> Self {
+        let orientation = match cells.as_slice() {
+            [first, second, ..] if first.1 == second.1 => Orientation::Vertical,
+            _ => Orientation::Horizontal,
+        };
+        Self {
+            id,
+            class: class.to_string(),
+            damage: vec![false; cells.len()],
+            cells,
+            orientation,
+            layer: Layer::Surface,
+        }
+    }
+
+    ///
+    /// Returns how many points the ship covers
+    ///
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    ///
+    /// Returns true if the ship covers no points
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    ///
+    /// Returns true if all of the ship's points are in one row or one column
+    ///
+    pub fn is_straight(&self) -> bool {
+        self.cells.iter().all(|&(r, _)| r == self.cells[0].0)
+            || self.cells.iter().all(|&(_, c)| c == self.cells[0].1)
+    }
+
+    ///
+    /// Returns how many of the ship's points have been hit
+    ///
+    pub fn hits(&self) -> usize {
+        self.damage.iter().filter(|&&hit| hit).count()
+    }
+
+    ///
+    /// Returns true once every point of the ship has been hit
+    ///
+    pub fn is_sunk(&self) -> bool {
+        self.damage.iter().all(|&hit| hit)
+    }
+
+    ///
+    /// Returns true if the ship covers the (row, column)
+    ///
+    pub fn covers(&self, point: (usize, usize)) -> bool {
+        self.cells.contains(&point)
+    }
+
+    ///
+    /// Returns true if the ship covers the (row, column) and it has been hit
+    ///
+    pub fn is_hit(&self, point: (usize, usize)) -> bool {
+        self.cells
+            .iter()
+            .zip(self.damage.iter())
+            .any(|(&cell, &hit)| cell == point && hit)
+    }
+
+    ///
+    /// Marks the (row, column) as hit
+    /// Returns false if the ship doesn't cover it
+    ///
+    pub fn hit(&mut self, point: (usize, usize)) -> bool {
+        match self.cells.iter().position(|&cell| cell == point) {
+            Some(i) => {
+                self.damage[i] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///
+    /// Marks the (row, column) as not hit, after a repair
+    /// Returns false if the ship doesn't cover it
+    ///
+    pub fn repair(&mut self, point: (usize, usize)) -> bool {
+        match self.cells.iter().position(|&cell| cell == point) {
+            Some(i) => {
+                self.damage[i] = false;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+///
+/// Every ship one side has placed, sunk or not, in the order they were placed
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fleet {
+    pub ships: Vec<Ship>,
+}
+
+impl Fleet {
+    ///
+    /// Creates an empty fleet
+    ///
+    pub fn new() -> Self {
+        Self { ships: vec![] }
+    }
+
+    ///
+    /// Arguements:
+    ///     class: &str - what kind of ship it is
+    ///     cells: Vec<(usize, usize)> - the points it covers
+    /// Adds an undamaged ship on the surface to the fleet and returns its id
+    ///
+    pub fn add(&mut self, class: &str, cells: Vec<(usize, usize)>) -> ShipId {
+        self.add_on(class, cells, Layer::Surface)
+    }
+
+    ///
+    /// Adds an undamaged ship on the given layer to the fleet and returns its id, like add
+    ///
+    pub fn add_on(&mut self, class: &str, cells: Vec<(usize, usize)>, layer: Layer) -> ShipId {
+        let next = self
+            .ships
+            .iter()
+            .map(|ship| ship.id.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let id = ShipId(next);
+        self.ships.push(Ship {
+            layer,
+            ..Ship::new(id, class, cells)
+        });
+        id
+    }
+
+    ///
+    /// Takes the ship of the given class out of the fleet, returning it if there was one
+    ///
+    pub fn remove(&mut self, class: &str) -> Option<Ship> {
+        let pos = self.ships.iter().position(|ship| ship.class == class)?;
+        Some(self.ships.remove(pos))
+    }
+
+    ///
+    /// Returns the ship with the given id, if it is still in the fleet
+    ///
+    pub fn ship(&self, id: ShipId) -> Option<&Ship> {
+        self.ships.iter().find(|ship| ship.id == id)
+    }
+
+    ///
+    /// Returns the ship of the given class, if there is one
+    ///
+    pub fn get(&self, class: &str) -> Option<&Ship> {
+        self.ships.iter().find(|ship| ship.class == class)
+    }
+
+    ///
+    /// Returns the ship covering the (row, column) on the layer, if there is one
+    ///
+    pub fn ship_at(&self, layer: Layer, point: (usize, usize)) -> Option<&Ship> {
+        self.ships
+            .iter()
+            .find(|ship| ship.layer == layer && ship.covers(point))
+    }
+
+    ///
+    /// Returns the ship covering the (row, column) on the layer, if there is one, to be
+    /// changed
+    ///
+    pub fn ship_at_mut(&mut self, layer: Layer, point: (usize, usize)) -> Option<&mut Ship> {
+        self.ships
+            .iter_mut()
+            .find(|ship| ship.layer == layer && ship.covers(point))
+    }
+
+    ///
+    /// Returns the ships on the layer, as a fleet of their own
+    ///
+    pub fn on_layer(&self, layer: Layer) -> Fleet {
+        Fleet {
+            ships: self
+                .ships
+                .iter()
+                .filter(|ship| ship.layer == layer)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    ///
+    /// Returns every ship that has not been sunk
+    ///
+    pub fn afloat(&self) -> Vec<&Ship> {
+        self.ships.iter().filter(|ship| !ship.is_sunk()).collect()
+    }
+
+    ///
+    /// Returns true once every ship has been sunk, or if there are none
+    ///
+    pub fn all_sunk(&self) -> bool {
+        self.ships.iter().all(|ship| ship.is_sunk())
+    }
+
+    ///
+    /// Returns how many ships there are, sunk or not
+    ///
+    pub fn len(&self) -> usize {
+        self.ships.len()
+    }
+
+    ///
+    /// Returns true if no ships have been placed
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.ships.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fleet() {
+        let mut fleet = Fleet::new();
+        assert!(fleet.all_sunk());
+        let destroyer = fleet.add("Destroyer", vec![(0, 0), (0, 1)]);
+        let cruiser = fleet.add("Cruiser", vec![(2, 4), (3, 4), (4, 4)]);
+        assert_eq!((destroyer, cruiser), (ShipId(0), ShipId(1)));
+        assert_eq!(fleet.ship(cruiser).unwrap().class, "Cruiser");
+        assert_eq!(
+            fleet.get("Destroyer").unwrap().orientation,
+            Orientation::Horizontal
+        );
+        assert_eq!(
+            fleet.get("Cruiser").unwrap().orientation,
+            Orientation::Vertical
+        );
+
+        let surface = Layer::Surface;
+        assert!(fleet.ship_at_mut(surface, (0, 1)).unwrap().hit((0, 1)));
+        assert!(!fleet.ship_at_mut(surface, (0, 1)).unwrap().hit((5, 5)));
+        assert_eq!(fleet.get("Destroyer").unwrap().hits(), 1);
+        fleet.ship_at_mut(surface, (0, 0)).unwrap().hit((0, 0));
+        let sunk = fleet.get("Destroyer").unwrap();
+        assert!(sunk.is_sunk());
+        assert!(sunk.is_hit((0, 0)) && !sunk.is_hit((5, 5)));
+        assert_eq!(sunk.cells, vec![(0, 0), (0, 1)]);
+        assert_eq!(fleet.afloat().len(), 1);
+        assert!(!fleet.all_sunk());
+
+        assert_eq!(fleet.remove("Cruiser").unwrap().id, cruiser);
+        assert!(fleet.ship(cruiser).is_none());
+        assert!(fleet.all_sunk());
+        assert!(fleet.ship_at(surface, (3, 4)).is_none());
+
+        fleet.add_on("Submarine", vec![(3, 4), (3, 5), (3, 6)], Layer::Deep);
+        assert!(fleet.ship_at(surface, (3, 4)).is_none());
+        assert_eq!(
+            fleet.ship_at(Layer::Deep, (3, 4)).unwrap().class,
+            "Submarine"
+        );
+        assert_eq!(fleet.on_layer(Layer::Deep).len(), 1);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::difficulty::Difficulty;
+use crate::grid::Grid;
+use crate::placement::PlacementStyle;
+use crate::possible_guess::Guesser;
+use crate::rules::Rules;
+
+///
+/// Plays one game for a computer opponent against a randomly placed fleet,
+/// without printing anything.
+/// Arguements:
+///     guesser: &mut dyn Guesser - the opponent to play
+///     rules: Rules - the rules the fleet is placed by
+/// Returns how many guesses it took to sink every ship.
+/// Stops at 200 guesses, or once the guesser has nothing left to guess, in case a
+/// guesser never finishes.
+///
+pub fn shots_to_win(guesser: &mut dyn Guesser, rules: Rules) -> i32 {
+    let mut grid = Grid::new();
+    grid.quiet = true;
+    grid.shaped = rules.shaped;
+    grid.topology = rules.topology;
+    grid.set_computer_ships(PlacementStyle::Random)
+        .expect("error: unable to place the computer's ships");
+
+    let mut shots = 0;
+    while shots < 200 {
+        shots += 1;
+        match grid.make_computer_guess(guesser) {
+            Ok(false) => {}
+            Ok(true) | Err(_) => break,
+        }
+    }
+    shots
+}
+
+///
+/// Plays a number of simulated games at each level
+/// Arguements:
+///     levels: &[Difficulty] - the levels to compare
+///     games: usize - how many games each level plays
+///     rules: Rules - the rules the games are played by
+/// Returns each level with its average number of guesses to win
+///
+pub fn benchmark(levels: &[Difficulty], games: usize, rules: Rules) -> Vec<(Difficulty, f32)> {
+    let mut results = vec![];
+    for level in levels {
+        let mut total = 0;
+        for _ in 0..games {
+            let mut guesser = level.settings().opponent();
+            guesser.play_by(rules);
+            total += shots_to_win(guesser.as_mut(), rules);
+        }
+        results.push((*level, total as f32 / games.max(1) as f32));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::possible_guess::PossibleGuesses;
+
+    #[test]
+    fn test_shots_to_win() {
+        let mut pg = PossibleGuesses::with_difficulty(Difficulty::Beginner);
+        let shots = shots_to_win(&mut pg, Rules::default());
+        assert!((17..=100).contains(&shots));
+        assert_eq!(pg.guess_count, shots);
+
+        let results = benchmark(&[Difficulty::Easy, Difficulty::Hard], 2, Rules::default());
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, avg)| *avg >= 17. && *avg <= 100.));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::coord::get_coord_index;
+use crate::grid::Grid;
+use crate::layers::Layer;
+use crate::rng::random;
+use std::fmt::{self, Display};
+
+///
+/// How many sonar scans each player starts the game with
+///
+pub const SCANS: i32 = 1;
+
+///
+/// The area a sonar scan covers, centered on the point it is aimed at
+///     Square: the 3 x 3 square around the point
+///     Plus: the point and the 2 points beyond it in each direction, across and down
+/// Both cover 9 points, fewer at the edge of the board
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ScanShape {
+    #[default]
+    Square,
+    Plus,
+}
+
+impl ScanShape {
+    ///
+    /// Arguements:
+    ///     input: &str - square (or 3x3) or plus (or +), not case sensitive
+    /// Returns the shape, or None if it isn't one
+    ///
+    pub fn from_input(input: &str) -> Option<ScanShape> {
+        match input.trim().to_lowercase().as_str() {
+            "square" | "3x3" => Some(ScanShape::Square),
+            "plus" | "+" => Some(ScanShape::Plus),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the (d_row, d_col) of every point the shape covers, from its center
+    ///
+    fn offsets(&self) -> Vec<(i32, i32)> {
+        match self {
+            ScanShape::Square => (-1..=1)
+                .flat_map(|r| (-1..=1).map(move |c| (r, c)))
+                .collect(),
+            ScanShape::Plus => (-2..=2)
+                .map(|r| (r, 0))
+                .chain((-2..=2).filter(|&c| c != 0).map(|c| (0, c)))
+                .collect(),
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     center: (i32, char) - the (row, column) the scan is aimed at
+    /// Returns the (row, column) of every point the scan covers that is on the board
+    ///
+    pub fn area(&self, center: (i32, char)) -> Vec<(i32, char)> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let (r_idx, c_idx) = get_coord_index(center.0, center.1);
+        self.offsets()
+            .into_iter()
+            .map(|(d_row, d_col)| (r_idx as i32 + d_row, c_idx as i32 + d_col))
+            .filter(|(r, c)| (0..10).contains(r) && (0..10).contains(c))
+            .map(|(r, c)| (r + 1, columns[c as usize]))
+            .collect()
+    }
+}
+
+impl Display for ScanShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanShape::Square => f.pad("3 x 3 square"),
+            ScanShape::Plus => f.pad("plus"),
+        }
+    }
+}
+
+impl Grid {
+    ///
+    /// Arguements:
+    ///     center: (i32, char) - the (row, column) the scan is aimed at
+    ///     shape: ScanShape - the area it covers
+    /// Returns how many points in the area have a ship on them, hit or not, without
+    /// saying which ones
+    ///
+    pub(crate) fn sonar_scan(&self, center: (i32, char), shape: ScanShape) -> usize {
+        shape
+            .area(center)
+            .into_iter()
+            .filter(|&(row, col)| {
+                self.fleet
+                    .ship_at(Layer::Surface, get_coord_index(row, col))
+                    .is_some()
+            })
+            .count()
+    }
+}
+
+///
+/// Arguements:
+///     open: &[(char, i32)] - the (col, row) of every point that hasn't been guessed
+/// Returns the (col, row) to aim a square scan at: the open point whose square covers the
+/// most open points (ties are random), or None if there are no open points
+///
+pub fn best_scan_center(open: &[(char, i32)]) -> Option<(char, i32)> {
+    let covered = |&(col, row): &(char, i32)| {
+        ScanShape::Square
+            .area((row, col))
+            .iter()
+            .filter(|&&(r, c)| open.contains(&(c, r)))
+            .count()
+    };
+    let most = open.iter().map(covered).max()?;
+    let best: Vec<(char, i32)> = open
+        .iter()
+        .copied()
+        .filter(|point| covered(point) == most)
+        .collect();
+    Some(best[(random::<f32>() * best.len() as f32).floor() as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sonar_scan() {
+        assert_eq!(ScanShape::Square.area((5, 'E')).len(), 9);
+        assert_eq!(ScanShape::Plus.area((5, 'E')).len(), 9);
+        assert_eq!(ScanShape::Square.area((1, 'A')).len(), 4);
+        assert_eq!(ScanShape::Plus.area((1, 'A')).len(), 5);
+        assert!(ScanShape::Plus.area((5, 'E')).contains(&(5, 'G')));
+        assert!(!ScanShape::Plus.area((5, 'E')).contains(&(6, 'F')));
+        assert_eq!(ScanShape::from_input("Plus"), Some(ScanShape::Plus));
+
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.create_ship(
+            ("Battleship".to_string(), 4),
+            &mut vec![(4, 2), (4, 3), (4, 4), (4, 5)],
+        )
+        .unwrap();
+        assert_eq!(grid.sonar_scan((5, 'E'), ScanShape::Square), 3);
+        assert_eq!(grid.sonar_scan((5, 'E'), ScanShape::Plus), 4);
+        grid.guess(5, 'D');
+        assert_eq!(grid.sonar_scan((5, 'E'), ScanShape::Square), 3);
+        assert_eq!(grid.sonar_scan((9, 'E'), ScanShape::Square), 0);
+
+        let open = [('A', 1), ('B', 2), ('C', 2), ('J', 10)];
+        assert_eq!(best_scan_center(&open), Some(('B', 2)));
+        assert_eq!(best_scan_center(&[]), None);
+    }
+}
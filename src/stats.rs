@@ -0,0 +1,343 @@
+use crate::difficulty::Difficulty;
+use crate::grid::Shot;
+use crate::profile::data_dir;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path, path::PathBuf};
+
+///
+/// What one side did over a single game, worked out from the shots it made
+/// shots: how many guesses were made
+/// hits: how many of them hit a ship
+/// longest_streak: the most hits in a row
+/// first_sink: the guess number the first ship was sunk on, if one was
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameTally {
+    pub shots: i32,
+    pub hits: i32,
+    pub longest_streak: i32,
+    pub first_sink: Option<i32>,
+}
+
+impl GameTally {
+    ///
+    /// Arguements:
+    ///     history: &[Shot] - every guess one side made, in order, as kept in Grid::history
+    ///         of the board they were guessing on
+    /// Returns the tally of those guesses
+    ///
+    pub fn from_history(history: &[Shot]) -> Self {
+        let mut tally = GameTally::default();
+        let mut streak = 0;
+
+        for shot in history {
+            let (hit, (sunk, _)) = shot.report;
+            tally.shots += 1;
+            if hit == 'h' {
+                tally.hits += 1;
+                streak += 1;
+                tally.longest_streak = tally.longest_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+            if (sunk == 's' || sunk == 'f') && tally.first_sink.is_none() {
+                tally.first_sink = Some(tally.shots);
+            }
+        }
+        tally
+    }
+
+    ///
+    /// Returns the share of shots that hit, from 0 to 100
+    ///
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.
+        } else {
+            self.hits as f32 * 100. / self.shots as f32
+        }
+    }
+}
+
+///
+/// One player's record at one difficulty level
+/// wins and losses are the number of games won and lost
+/// win_shots is the total shots taken over every win, for the average
+/// best_win is the fewest shots taken to win a game
+/// shots and hits are totals over every game, for the hit accuracy
+/// longest_streak is the most hits in a row in any game
+/// fastest_sink is the earliest guess in any game that sunk a ship
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelStats {
+    pub wins: i32,
+    pub losses: i32,
+    pub win_shots: i32,
+    pub best_win: Option<i32>,
+    pub shots: i32,
+    pub hits: i32,
+    pub longest_streak: i32,
+    pub fastest_sink: Option<i32>,
+}
+
+impl LevelStats {
+    ///
+    /// Adds a finished game to the record
+    ///
+    pub fn record(&mut self, won: bool, tally: &GameTally) {
+        if won {
+            self.wins += 1;
+            self.win_shots += tally.shots;
+            self.best_win = Some(match self.best_win {
+                Some(best) => best.min(tally.shots),
+                None => tally.shots,
+            });
+        } else {
+            self.losses += 1;
+        }
+        self.shots += tally.shots;
+        self.hits += tally.hits;
+        self.longest_streak = self.longest_streak.max(tally.longest_streak);
+        if let Some(sink) = tally.first_sink {
+            self.fastest_sink = Some(match self.fastest_sink {
+                Some(fastest) => fastest.min(sink),
+                None => sink,
+            });
+        }
+    }
+
+    ///
+    /// Adds another record into this one, for totals across levels
+    ///
+    fn combine(&mut self, other: &LevelStats) {
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.win_shots += other.win_shots;
+        self.shots += other.shots;
+        self.hits += other.hits;
+        self.longest_streak = self.longest_streak.max(other.longest_streak);
+        for (mine, theirs) in [
+            (&mut self.best_win, other.best_win),
+            (&mut self.fastest_sink, other.fastest_sink),
+        ] {
+            if let Some(theirs) = theirs {
+                *mine = Some(mine.map_or(theirs, |m| m.min(theirs)));
+            }
+        }
+    }
+
+    ///
+    /// Returns one row of the stats table, after the name and level columns
+    ///
+    fn row(&self) -> String {
+        let games = self.wins + self.losses;
+        let win_rate = if games == 0 {
+            0.
+        } else {
+            self.wins as f32 * 100. / games as f32
+        };
+        let average = if self.wins == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}", self.win_shots as f32 / self.wins as f32)
+        };
+        let accuracy = if self.shots == 0 {
+            0.
+        } else {
+            self.hits as f32 * 100. / self.shots as f32
+        };
+        let best = self.best_win.map_or("-".to_string(), |b| b.to_string());
+        let sink = self.fastest_sink.map_or("-".to_string(), |s| s.to_string());
+        format!(
+            "{:>4} {:>4} {:>5.0}% {:>7} {:>5} {:>8.1}% {:>7} {:>7}",
+            self.wins, self.losses, win_rate, average, best, accuracy, self.longest_streak, sink
+        )
+    }
+}
+
+///
+/// Every player's record, by name and then by difficulty level name.
+/// Saved as JSON.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsStore {
+    pub players: BTreeMap<String, BTreeMap<String, LevelStats>>,
+}
+
+impl StatsStore {
+    ///
+    /// The file stats are stored in, stats.json in data_dir()
+    ///
+    pub fn default_path() -> PathBuf {
+        data_dir().join("stats.json")
+    }
+
+    ///
+    /// Returns the stats saved at path, or an empty store if there are none
+    /// or they can't be read
+    ///
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+            Err(_) => StatsStore::default(),
+        }
+    }
+
+    ///
+    /// Saves the stats to path, creating its folder if needed
+    ///
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    ///
+    /// Arguements:
+    ///     name: &str - the player's name
+    ///     level: Difficulty - the level the game was played at
+    ///     won: bool - whether the player won
+    ///     tally: &GameTally - the player's shots over the game
+    /// Adds a finished game to the player's record
+    ///
+    pub fn record(&mut self, name: &str, level: Difficulty, won: bool, tally: &GameTally) {
+        self.players
+            .entry(name.to_string())
+            .or_default()
+            .entry(level.to_string())
+            .or_default()
+            .record(won, tally);
+    }
+
+    ///
+    /// Returns the stats as printable tables:
+    /// a leaderboard of every player's totals, most wins first,
+    /// then every player's record at each level
+    ///
+    pub fn tables(&self) -> String {
+        let header = format!(
+            "{:<16} {:<10} {:>4} {:>4} {:>6} {:>7} {:>5} {:>9} {:>7} {:>7}",
+            "Player",
+            "Level",
+            "Won",
+            "Lost",
+            "Win%",
+            "AvgWin",
+            "Best",
+            "Accuracy",
+            "Streak",
+            "Sink"
+        );
+        let mut out = String::new();
+
+        let mut totals: Vec<(&String, LevelStats)> = self
+            .players
+            .iter()
+            .map(|(name, levels)| {
+                let mut total = LevelStats::default();
+                for stats in levels.values() {
+                    total.combine(stats);
+                }
+                (name, total)
+            })
+            .collect();
+        totals.sort_by(|a, b| b.1.wins.cmp(&a.1.wins).then(a.1.losses.cmp(&b.1.losses)));
+
+        out.push_str("Leaderboard\n");
+        out.push_str(&header);
+        out.push('\n');
+        for (name, total) in &totals {
+            out.push_str(&format!("{:<16} {:<10} {}\n", name, "All", total.row()));
+        }
+
+        out.push_str("\nBy difficulty\n");
+        out.push_str(&header);
+        out.push('\n');
+        for (name, levels) in &self.players {
+            for level in Difficulty::all() {
+                if let Some(stats) = levels.get(&level.to_string()) {
+                    out.push_str(&format!("{:<16} {:<10} {}\n", name, level, stats.row()));
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::Layer;
+
+    fn shot(report: (char, (char, i32))) -> Shot {
+        Shot {
+            row: 1,
+            column: 'A',
+            report,
+            ship: None,
+            layer: Layer::Surface,
+        }
+    }
+
+    #[test]
+    fn test_tally() {
+        let history = vec![
+            shot(('m', ('n', 0))),
+            shot(('h', ('u', 0))),
+            shot(('h', ('s', 2))),
+            shot(('m', ('n', 0))),
+            shot(('h', ('u', 0))),
+        ];
+        let tally = GameTally::from_history(&history);
+        assert_eq!(tally.shots, 5);
+        assert_eq!(tally.hits, 3);
+        assert_eq!(tally.longest_streak, 2);
+        assert_eq!(tally.first_sink, Some(3));
+        assert_eq!(tally.accuracy(), 60.);
+    }
+
+    #[test]
+    fn test_store() {
+        let path = std::env::temp_dir()
+            .join(format!("battleship-stats-{}", std::process::id()))
+            .join("stats.json");
+        let win = GameTally {
+            shots: 50,
+            hits: 17,
+            longest_streak: 4,
+            first_sink: Some(9),
+        };
+        let loss = GameTally {
+            shots: 60,
+            hits: 10,
+            longest_streak: 2,
+            first_sink: None,
+        };
+
+        let mut store = StatsStore::load(&path);
+        store.record("Alex", Difficulty::Hard, true, &win);
+        store.record("Alex", Difficulty::Hard, false, &loss);
+        store.record("Robin", Difficulty::Easy, false, &loss);
+        store.save(&path).unwrap();
+
+        let loaded = StatsStore::load(&path);
+        assert_eq!(loaded, store);
+        let hard = &loaded.players["Alex"]["Hard"];
+        assert_eq!(hard.wins, 1);
+        assert_eq!(hard.losses, 1);
+        assert_eq!(hard.best_win, Some(50));
+        assert_eq!(hard.hits, 27);
+        assert_eq!(hard.longest_streak, 4);
+        assert_eq!(hard.fastest_sink, Some(9));
+
+        let tables = loaded.tables();
+        let leaderboard: Vec<&str> = tables.lines().collect();
+        assert!(leaderboard[2].starts_with("Alex"));
+        assert!(leaderboard[3].starts_with("Robin"));
+        assert!(tables.contains("By difficulty"));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+use crate::coord::get_coord_index;
+use crate::grid::{Grid, Shot};
+use crate::layers::Layer;
+use crate::ship::Fleet;
+use crate::stats::GameTally;
+
+///
+/// The shades used for the heatmap, from the first quarter of the game's shots
+/// to the last
+///
+const HEAT: [&str; 4] = ["██", "▓▓", "▒▒", "░░"];
+
+///
+/// Arguements:
+///     history: &[Shot] - every guess one side made, in order
+///     fleet: &Fleet - the fleet they fired at, which names the ships
+/// Returns a line for each ship that was sunk, in the order they sank, with the shot
+/// it sank on and how many shots that took from the first hit on it
+///
+fn sink_lines(history: &[Shot], fleet: &Fleet) -> Vec<String> {
+    let mut lines = vec![];
+    for (i, shot) in history.iter().enumerate() {
+        let (_, (sunk, _)) = shot.report;
+        if sunk != 's' && sunk != 'f' {
+            continue;
+        }
+        if let Some(ship) = shot.ship.and_then(|id| fleet.ship(id)) {
+            let name = &ship.class;
+            let first_hit = history
+                .iter()
+                .position(|other| other.ship == Some(ship.id))
+                .unwrap_or(i);
+            lines.push(format!(
+                "    {name:<10} sunk on shot {:>3}, {} shots after the first hit",
+                i + 1,
+                i - first_hit + 1
+            ));
+        }
+    }
+    if lines.is_empty() {
+        lines.push("    No ships sunk".to_string());
+    }
+    lines
+}
+
+///
+/// Arguements:
+///     history: &[Shot] - every guess one side made, in order
+///     layer: Layer - the layer of the board to draw
+/// Returns the layer as lines of text, shading each point by when it was fired at.
+/// The darkest shade is the first quarter of all the shots, the lightest the last.
+/// Points that were never fired at are left blank.
+///
+fn heatmap_rows(history: &[Shot], layer: Layer) -> Vec<String> {
+    let mut order = vec![vec![None; 10]; 10];
+    for (i, shot) in history.iter().enumerate() {
+        if shot.layer != layer {
+            continue;
+        }
+        let (r, c) = get_coord_index(shot.row, shot.column);
+        if order[r][c].is_none() {
+            order[r][c] = Some(i);
+        }
+    }
+
+    let mut rows = vec!["   A  B  C  D  E  F  G  H  I  J ".to_string()];
+    for (r, row) in order.iter().enumerate() {
+        let mut line = format!("{:>2} ", r + 1);
+        for point in row {
+            match point {
+                Some(i) => {
+                    let quarter = i * 4 / history.len().max(1);
+                    line.push_str(HEAT[quarter.min(3)]);
+                    line.push(' ');
+                }
+                None => line.push_str("·  "),
+            }
+        }
+        rows.push(line);
+    }
+    rows
+}
+
+///
+/// Arguements:
+///     name: &str - who made the shots
+///     history: &[Shot] - every guess they made, in order
+///     fleet: &Fleet - the fleet they fired at
+/// Returns the lines of the summary for one side
+///
+fn side_lines(name: &str, history: &[Shot], fleet: &Fleet) -> Vec<String> {
+    let tally = GameTally::from_history(history);
+    let mut lines = vec![
+        format!("{name}:"),
+        format!(
+            "    {} shots, {} hits, {} misses, {:.1}% accuracy, longest streak {}",
+            tally.shots,
+            tally.hits,
+            tally.shots - tally.hits,
+            tally.accuracy(),
+            tally.longest_streak
+        ),
+    ];
+    lines.extend(sink_lines(history, fleet));
+    lines
+}
+
+///
+/// Arguements:
+///     user_grid: &Grid - the user's board, which the computer fired at
+///     computer_grid: &Grid - the computer's board, which the user fired at
+/// Returns the end of game summary:
+///     total shots, hits, misses and accuracy for each side
+///     the shot each ship was sunk on
+///     both fleets revealed side by side
+///     a heatmap of when the computer fired at each point, for each layer
+///
+pub fn game_summary(user_grid: &Grid, computer_grid: &Grid) -> String {
+    let mut lines = vec![
+        "Game summary".to_string(),
+        format!(
+            "Total shots: {}",
+            user_grid.history.len() + computer_grid.history.len()
+        ),
+    ];
+    lines.extend(side_lines(
+        "You",
+        &computer_grid.history,
+        &computer_grid.fleet,
+    ));
+    lines.extend(side_lines("Computer", &user_grid.history, &user_grid.fleet));
+    lines.push(String::new());
+
+    lines.push(format!("{:<36}{}", "Your fleet:", "Computer's fleet:"));
+    for (mine, theirs) in user_grid
+        .reveal_rows()
+        .iter()
+        .zip(computer_grid.reveal_rows().iter())
+    {
+        lines.push(format!("{mine}   {theirs}"));
+    }
+    lines.push(String::new());
+
+    lines.push("Where the computer fired, darkest first:".to_string());
+    for layer in user_grid.layers() {
+        if user_grid.is_layered() {
+            lines.push(layer.to_string());
+        }
+        lines.extend(heatmap_rows(&user_grid.history, layer));
+    }
+
+    let mut summary = lines.join("\n");
+    summary.push('\n');
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::PlacementStyle;
+
+    #[test]
+    fn test_game_summary() {
+        let mut user_grid = Grid::new();
+        user_grid.quiet = true;
+        user_grid
+            .set_computer_ships(PlacementStyle::Random)
+            .unwrap();
+        let mut computer_grid = Grid::new();
+        computer_grid.quiet = true;
+        computer_grid
+            .set_computer_ships(PlacementStyle::Random)
+            .unwrap();
+
+        let destroyer = computer_grid.fleet.get("Destroyer").unwrap().cells.clone();
+        let columns = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        for (r, c) in destroyer {
+            computer_grid.guess(r as i32 + 1, columns[c]);
+        }
+        user_grid.guess(1, 'A');
+        user_grid.guess(10, 'J');
+
+        let summary = game_summary(&user_grid, &computer_grid);
+        assert!(summary.contains("Total shots: 4"));
+        assert!(summary.contains("2 shots, 2 hits, 0 misses, 100.0% accuracy"));
+        assert!(summary.contains("Destroyer  sunk on shot   2, 2 shots after the first hit"));
+        assert!(summary.contains("Computer's fleet:"));
+
+        let heat = heatmap_rows(&user_grid.history, Layer::Surface);
+        assert!(heat[1].starts_with(" 1 ██"));
+        assert!(heat[10].ends_with("▒▒ "));
+        assert!(heatmap_rows(&user_grid.history, Layer::Deep)[1].starts_with(" 1 ·"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::coord::{CoordState, HIDDEN_SHIP};
+use std::{
+    cell::Cell,
+    fmt::{self, Display},
+};
+
+thread_local! {
+    static CURRENT: Cell<Theme> = const { Cell::new(Theme::Emoji) };
+}
+
+///
+/// How boards are drawn
+///     Emoji: coloured squares
+///     Ascii: plain characters, for terminals without emoji
+/// Every symbol is two columns wide, so boards line up the same in either theme
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Emoji,
+    Ascii,
+}
+
+impl Theme {
+    ///
+    /// Every theme
+    ///
+    pub fn all() -> Vec<Theme> {
+        vec![Theme::Emoji, Theme::Ascii]
+    }
+
+    ///
+    /// Arguements:
+    ///     input: &str - the theme's name, not case sensitive
+    /// Returns the matching theme, or None if the input is not a theme
+    ///
+    pub fn from_input(input: &str) -> Option<Theme> {
+        let input = input.trim().to_lowercase();
+        Theme::all()
+            .into_iter()
+            .find(|theme| theme.to_string().to_lowercase() == input)
+    }
+
+    ///
+    /// Returns how a point in the given state is drawn
+    ///
+    pub fn symbol(&self, state: CoordState) -> &'static str {
+        match (self, state) {
+            (Theme::Emoji, CoordState::Blank) => "🟦",
+            (Theme::Emoji, CoordState::Hit) => "🟥",
+            (Theme::Emoji, CoordState::Miss) => "🟩",
+            (Theme::Emoji, CoordState::Ship) => "🟨",
+            (Theme::Emoji, CoordState::Mine) => "💣",
+            (Theme::Emoji, CoordState::Decoy) => "🎈",
+            (Theme::Emoji, CoordState::Detonated) => "💥",
+            (Theme::Emoji, CoordState::Uncertain) => "🟧",
+            (Theme::Ascii, CoordState::Blank) => "~~",
+            (Theme::Ascii, CoordState::Hit) => "XX",
+            (Theme::Ascii, CoordState::Miss) => "..",
+            (Theme::Ascii, CoordState::Ship) => "##",
+            (Theme::Ascii, CoordState::Mine) => "<>",
+            (Theme::Ascii, CoordState::Decoy) => "%%",
+            (Theme::Ascii, CoordState::Detonated) => "**",
+            (Theme::Ascii, CoordState::Uncertain) => "??",
+        }
+    }
+
+    ///
+    /// Returns how a ship point the other player never found is drawn when a
+    /// board is revealed
+    ///
+    pub fn hidden_ship(&self) -> &'static str {
+        match self {
+            Theme::Emoji => HIDDEN_SHIP,
+            Theme::Ascii => "[]",
+        }
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Emoji => f.pad("Emoji"),
+            Theme::Ascii => f.pad("Ascii"),
+        }
+    }
+}
+
+///
+/// Sets the theme every board on this thread is drawn with
+///
+pub fn set_theme(theme: Theme) {
+    CURRENT.with(|current| current.set(theme));
+}
+
+///
+/// Returns the theme boards are being drawn with
+///
+pub fn current() -> Theme {
+    CURRENT.with(|current| current.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::Coordinate;
+
+    #[test]
+    fn test_theme() {
+        assert_eq!(Theme::from_input("ASCII\n"), Some(Theme::Ascii));
+        assert_eq!(Theme::from_input("neon"), None);
+
+        let hit = Coordinate {
+            state: CoordState::Hit,
+        };
+        assert_eq!(hit.to_string(), "🟥");
+        set_theme(Theme::Ascii);
+        assert_eq!(hit.to_string(), "XX");
+        assert_eq!(current().hidden_ship(), "[]");
+        set_theme(Theme::Emoji);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::grid::Grid;
+use crate::layers::Layer;
+use std::fmt::{self, Display};
+
+///
+/// How the points of the board fit together
+///     Square: the usual grid, where each point touches the 4 across and down from it
+///     Hex: a board of hexagons, where each point touches 6. Points are in axial
+///         coordinates: the column is one axis and the row another, and each row is drawn
+///         half a point to the right of the one above, so the board is a rhombus.
+///         Ships can lie across, down, or along the third axis, down and to the left.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    #[default]
+    Square,
+    Hex,
+}
+
+impl Topology {
+    ///
+    /// Every topology
+    ///
+    pub fn all() -> Vec<Topology> {
+        vec![Topology::Square, Topology::Hex]
+    }
+
+    ///
+    /// Arguements:
+    ///     input: &str - the topology's name, not case sensitive
+    /// Returns the matching topology, or None if the input is not one
+    ///
+    pub fn from_input(input: &str) -> Option<Topology> {
+        let input = input.trim().to_lowercase();
+        Topology::all()
+            .into_iter()
+            .find(|topology| topology.to_string().to_lowercase() == input)
+    }
+
+    ///
+    /// Returns the directions a straight ship can lie in, as (rows, columns) for one step.
+    /// Down comes first and across second.
+    ///
+    pub fn axes(&self) -> Vec<(i32, i32)> {
+        match self {
+            Topology::Square => vec![(1, 0), (0, 1)],
+            Topology::Hex => vec![(1, 0), (0, 1), (1, -1)],
+        }
+    }
+
+    ///
+    /// Returns the steps, as (rows, columns), to every point touching a point: right,
+    /// left, down and up, then on a hex board down left and up right
+    ///
+    pub fn steps(&self) -> Vec<(i32, i32)> {
+        let mut steps = vec![(0, 1), (0, -1), (1, 0), (-1, 0)];
+        if *self == Topology::Hex {
+            steps.extend([(1, -1), (-1, 1)]);
+        }
+        steps
+    }
+
+    ///
+    /// Arguements:
+    ///     point: (usize, usize) - a (row, column) on the board
+    /// Returns the (row, column) of every point on the board touching it, in the order
+    /// of steps
+    ///
+    pub fn neighbors(&self, point: (usize, usize)) -> Vec<(usize, usize)> {
+        self.steps()
+            .into_iter()
+            .map(|(d_row, d_col)| (point.0 as i32 + d_row, point.1 as i32 + d_col))
+            .filter(|&(r, c)| (0..10).contains(&r) && (0..10).contains(&c))
+            .map(|(r, c)| (r as usize, c as usize))
+            .collect()
+    }
+
+    ///
+    /// Arguements:
+    ///     row: i32, col: i32 - a point
+    ///     spacing: i32 - how many classes there are
+    /// Returns the parity class of the point. A straight ship at least spacing long
+    /// covers every class, along any axis, as long as the spacing is 1 or, on a hex
+    /// board, odd.
+    ///     Square: (row + column) % spacing
+    ///     Hex: (row + 2 * column) % spacing
+    ///
+    pub fn class(&self, row: i32, col: i32, spacing: i32) -> usize {
+        match self {
+            Topology::Square => (row + col).rem_euclid(spacing) as usize,
+            Topology::Hex => (row + 2 * col).rem_euclid(spacing) as usize,
+        }
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::Square => f.pad("Square"),
+            Topology::Hex => f.pad("Hex"),
+        }
+    }
+}
+
+impl Grid {
+    ///
+    /// Returns one layer of the board drawn as hexagons, one line per row: each row is
+    /// shifted half a point further right than the one above, so every point sits between
+    /// the two above it that it touches
+    ///
+    pub(crate) fn hex_rows(&self, layer: Layer) -> Vec<String> {
+        let mut rows = vec!["   A   B   C   D   E   F   G   H   I   J".to_string()];
+        for (r, row) in self.grid[layer.index()].iter().enumerate() {
+            let mut line = format!("{:>2} {}", r + 1, " ".repeat(2 * r));
+            for space in row {
+                line.push_str(&format!("{space}  "));
+            }
+            rows.push(line.trim_end().to_string());
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topology() {
+        assert_eq!(Topology::from_input(" HEX"), Some(Topology::Hex));
+        assert_eq!(Topology::from_input("round"), None);
+
+        assert_eq!(Topology::Square.neighbors((0, 0)), vec![(0, 1), (1, 0)]);
+        assert_eq!(Topology::Square.neighbors((4, 4)).len(), 4);
+        assert_eq!(Topology::Hex.neighbors((4, 4)).len(), 6);
+        assert_eq!(
+            Topology::Hex.neighbors((0, 5)),
+            vec![(0, 6), (0, 4), (1, 5), (1, 4)]
+        );
+
+        // a line of 3 along any hex axis covers every class of 3
+        for (d_row, d_col) in Topology::Hex.axes() {
+            let mut classes: Vec<usize> = (0..3)
+                .map(|i| Topology::Hex.class(4 + d_row * i, 4 + d_col * i, 3))
+                .collect();
+            classes.sort();
+            assert_eq!(classes, vec![0, 1, 2]);
+        }
+
+        let rows = Grid::new().hex_rows(Layer::Surface);
+        assert_eq!(rows.len(), 11);
+        assert!(rows[10].starts_with(&format!("10 {}", " ".repeat(18))));
+    }
+}
@@ -0,0 +1,837 @@
+use crate::coord::*;
+use crate::grid::{Direction, Grid, Shot, FLEET};
+use crate::layers::Layer;
+use crate::possible_guess::Guesser;
+use crate::repair::REPAIRS;
+use crate::rules::Rules;
+use crate::ship::Fleet;
+use crate::sonar::{ScanShape, SCANS};
+use std::{
+    fmt::{self, Display},
+    time::Instant,
+};
+
+///
+/// The "Advanced Mission" special weapons
+///     Airstrike: hits a line of 3 points, from the target in a direction
+///     Bombardment: hits the 3 x 3 square around the target
+///     Torpedo: starts on an edge of the board and travels in a direction, missing
+///         every point it crosses until it strikes a ship, mine or decoy
+/// Each weapon can only be used while the ship that carries it is afloat
+/// With the layers rule a torpedo only reaches the deep layer, and the others only the
+/// surface
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Weapon {
+    Airstrike,
+    Bombardment,
+    Torpedo,
+}
+
+impl Weapon {
+    ///
+    /// Every weapon, in the order the computer prefers them
+    ///
+    pub fn all() -> Vec<Weapon> {
+        vec![Weapon::Bombardment, Weapon::Airstrike, Weapon::Torpedo]
+    }
+
+    ///
+    /// Returns the class of ship that carries the weapon
+    ///
+    pub fn granted_by(&self) -> &'static str {
+        match self {
+            Weapon::Airstrike => "Carrier",
+            Weapon::Bombardment => "Battleship",
+            Weapon::Torpedo => "Submarine",
+        }
+    }
+
+    ///
+    /// Returns how many times the weapon can be used in a game
+    ///
+    pub fn ammo(&self) -> i32 {
+        match self {
+            Weapon::Airstrike => 1,
+            Weapon::Bombardment => 1,
+            Weapon::Torpedo => 2,
+        }
+    }
+
+    ///
+    /// Returns the layer the weapon reaches, with the layers rule
+    ///
+    pub fn layer(&self) -> Layer {
+        match self {
+            Weapon::Torpedo => Layer::Deep,
+            Weapon::Airstrike | Weapon::Bombardment => Layer::Surface,
+        }
+    }
+
+    ///
+    /// Returns an example of how to use the weapon
+    ///
+    pub fn usage(&self) -> &'static str {
+        match self {
+            Weapon::Airstrike => "airstrike A5 right",
+            Weapon::Bombardment => "bombard E5",
+            Weapon::Torpedo => "torpedo A5 right",
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     input: &str - the weapon's name, or a short form of it, not case sensitive
+    /// Returns the weapon, or None if it isn't one
+    ///
+    pub fn from_input(input: &str) -> Option<Weapon> {
+        match input.trim().to_lowercase().as_str() {
+            "airstrike" | "air" => Some(Weapon::Airstrike),
+            "bombardment" | "bombard" | "bomb" => Some(Weapon::Bombardment),
+            "torpedo" | "torp" => Some(Weapon::Torpedo),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Weapon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Weapon::Airstrike => f.pad("Airstrike"),
+            Weapon::Bombardment => f.pad("Bombardment"),
+            Weapon::Torpedo => f.pad("Torpedo"),
+        }
+    }
+}
+
+///
+/// What the user entered on their turn
+///     Shot: a normal guess at a (row, column)
+///     Deep: a normal guess at a (row, column) on the deep layer, with the layers rule
+///     Special: a weapon, its target (row, column) and direction. Bombardment
+///         ignores the direction.
+///     Scan: a sonar scan centered on a (row, column)
+///     Move: moving one of the player's own ships, by class, one point in a direction
+///     Repair: repairing a hit on one of the player's own ships, at a (row, column)
+///     Invalid: anything else
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Attack {
+    Shot((i32, char)),
+    Deep((i32, char)),
+    Special(Weapon, (i32, char), Direction),
+    Scan((i32, char), ScanShape),
+    Move(&'static str, Direction),
+    Repair((i32, char)),
+    Invalid,
+}
+
+///
+/// Arguements:
+///     input: &str - a line the user entered on their turn
+/// Returns the attack it is, like A5, A5 deep, bombard E5, torpedo A5 right, sonar E5,
+/// sonar E5 plus, move Cruiser down or repair C3
+///
+pub fn parse_attack(input: &str) -> Attack {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let target = |word: &str| match parse_coord(word) {
+        (coord, true) => Some(coord),
+        _ => None,
+    };
+    let sonar = |word: &str| ["sonar", "scan"].contains(&word.to_lowercase().as_str());
+    let attack = match words.as_slice() {
+        [coord] => target(coord).map(Attack::Shot),
+        [word, coord] if sonar(word) => {
+            target(coord).map(|coord| Attack::Scan(coord, ScanShape::Square))
+        }
+        [word, coord] if word.eq_ignore_ascii_case("repair") => target(coord).map(Attack::Repair),
+        [word, class, direction] if word.eq_ignore_ascii_case("move") => {
+            let class = FLEET
+                .iter()
+                .map(|&(name, _)| name)
+                .find(|name| name.eq_ignore_ascii_case(class));
+            match (class, Direction::from_input(direction)) {
+                (Some(class), Some(direction)) => Some(Attack::Move(class, direction)),
+                _ => None,
+            }
+        }
+        [word, coord, shape] if sonar(word) => {
+            match (target(coord), ScanShape::from_input(shape)) {
+                (Some(coord), Some(shape)) => Some(Attack::Scan(coord, shape)),
+                _ => None,
+            }
+        }
+        [coord, layer] if target(coord).is_some() => match Layer::from_input(layer) {
+            Some(Layer::Deep) => target(coord).map(Attack::Deep),
+            Some(Layer::Surface) => target(coord).map(Attack::Shot),
+            None => None,
+        },
+        [weapon, coord] => match Weapon::from_input(weapon) {
+            Some(Weapon::Bombardment) => target(coord)
+                .map(|coord| Attack::Special(Weapon::Bombardment, coord, Direction::Right)),
+            _ => None,
+        },
+        [weapon, coord, direction] => match (
+            Weapon::from_input(weapon),
+            target(coord),
+            Direction::from_input(direction),
+        ) {
+            (Some(weapon), Some(coord), Some(direction)) if weapon != Weapon::Bombardment => {
+                Some(Attack::Special(weapon, coord, direction))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    attack.unwrap_or(Attack::Invalid)
+}
+
+///
+/// The special weapons one player has left
+/// ammo is how many more times each weapon can be used
+/// scans is how many more sonar scans can be made
+/// scan_every is how many turns after a scan another one is ready, or 0 if it never is
+/// scan_wait is how many more turns until the next scan is ready
+/// moves is whether ships can be moved instead of firing
+/// repairs is how many more hits can be repaired
+/// layered is whether the game has a deep layer, so the computer only uses weapons
+///     that reach the surface
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arsenal {
+    pub ammo: Vec<(Weapon, i32)>,
+    pub scans: i32,
+    pub scan_every: i32,
+    pub scan_wait: i32,
+    pub moves: bool,
+    pub repairs: i32,
+    pub layered: bool,
+}
+
+impl Arsenal {
+    ///
+    /// Creates a full arsenal, with every weapon's ammo for one game, no sonar scans, no
+    /// moves and no repairs
+    ///
+    pub fn new() -> Self {
+        Self {
+            ammo: Weapon::all()
+                .into_iter()
+                .map(|weapon| (weapon, weapon.ammo()))
+                .collect(),
+            scans: 0,
+            scan_every: 0,
+            scan_wait: 0,
+            moves: false,
+            repairs: 0,
+            layered: false,
+        }
+    }
+
+    ///
+    /// Creates the arsenal a player starts the game with under the rules: the weapons
+    /// if the advanced rules are on, sonar scans if sonar is on, moves if mobile is on
+    /// and repairs if repair is on
+    ///
+    pub fn for_rules(rules: Rules) -> Self {
+        let mut arsenal = Self::new();
+        if !rules.advanced {
+            for (_, ammo) in &mut arsenal.ammo {
+                *ammo = 0;
+            }
+        }
+        if rules.sonar {
+            arsenal.scans = SCANS;
+            arsenal.scan_every = rules.scan_every;
+        }
+        arsenal.moves = rules.mobile;
+        if rules.repair {
+            arsenal.repairs = REPAIRS;
+        }
+        arsenal.layered = rules.layers;
+        arsenal
+    }
+
+    ///
+    /// Returns how many more times the weapon can be used
+    ///
+    pub fn ammo_left(&self, weapon: Weapon) -> i32 {
+        self.ammo
+            .iter()
+            .find(|(w, _)| *w == weapon)
+            .map_or(0, |(_, ammo)| *ammo)
+    }
+
+    ///
+    /// Arguements:
+    ///     own: &Fleet - the fleet of the player using the arsenal
+    /// Returns every weapon with ammo left whose ship is still afloat
+    ///
+    pub fn available(&self, own: &Fleet) -> Vec<Weapon> {
+        Weapon::all()
+            .into_iter()
+            .filter(|weapon| self.ammo_left(*weapon) > 0)
+            .filter(|weapon| {
+                own.get(weapon.granted_by())
+                    .is_some_and(|ship| !ship.is_sunk())
+            })
+            .collect()
+    }
+
+    ///
+    /// Counts down to the next sonar scan at the start of the player's turn, when scans
+    /// come back every few turns and the last one has been used
+    ///
+    fn start_turn(&mut self) {
+        if self.scans == 0 && self.scan_every > 0 {
+            self.scan_wait -= 1;
+            if self.scan_wait <= 0 {
+                self.scans = 1;
+            }
+        }
+    }
+
+    ///
+    /// Uses up a sonar scan, and starts the wait for the next one
+    ///
+    fn spend_scan(&mut self) {
+        self.scans -= 1;
+        self.scan_wait = self.scan_every;
+    }
+
+    ///
+    /// Uses up one of the weapon's ammo
+    ///
+    fn spend(&mut self, weapon: Weapon) {
+        for (w, ammo) in &mut self.ammo {
+            if *w == weapon {
+                *ammo -= 1;
+            }
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     own: &Fleet - the computer's own fleet
+    ///     guess: (char, i32) - the (column, row) the computer's guesser picked
+    /// Returns the weapon, target and direction the computer attacks with, or None to
+    /// just fire at the guess.
+    /// The computer uses its weapons as soon as it can, best first, always covering
+    /// the guess: bombarding around it, an airstrike starting on it towards the middle
+    /// of the board, or a torpedo from the nearest side edge along its row.
+    /// With a deep layer it only uses weapons that reach the surface, where its guesser
+    /// is aiming.
+    ///
+    pub fn computer_attack(
+        &self,
+        own: &Fleet,
+        guess: (char, i32),
+    ) -> Option<(Weapon, (i32, char), Direction)> {
+        let (col, row) = guess;
+        let (_, c_idx) = get_coord_index(row, col);
+        let towards_middle = if c_idx < 5 {
+            Direction::Right
+        } else {
+            Direction::Left
+        };
+        let weapon = self
+            .available(own)
+            .into_iter()
+            .find(|weapon| !self.layered || weapon.layer() == Layer::Surface)?;
+        Some(match weapon {
+            Weapon::Bombardment | Weapon::Airstrike => (weapon, (row, col), towards_middle),
+            Weapon::Torpedo => {
+                let edge = if c_idx < 5 { 'A' } else { 'J' };
+                (weapon, (row, edge), towards_middle)
+            }
+        })
+    }
+
+    ///
+    /// Returns a line listing the weapons, sonar scans, moves and repairs that can be used
+    /// and how to use them, or None if there are none
+    ///
+    pub fn status(&self, own: &Fleet) -> Option<String> {
+        let mut weapons: Vec<String> = self
+            .available(own)
+            .iter()
+            .map(|weapon| format!("{weapon} x{} ({})", self.ammo_left(*weapon), weapon.usage()))
+            .collect();
+        if self.scans > 0 {
+            weapons.push(format!(
+                "Sonar x{} (sonar E5, or sonar E5 plus)",
+                self.scans
+            ));
+        }
+        if self.moves && own.ships.iter().any(|ship| ship.hits() == 0) {
+            weapons.push("Move a ship that hasn't been hit (move Cruiser down)".to_string());
+        }
+        if self.repairs > 0
+            && own
+                .ships
+                .iter()
+                .any(|ship| ship.hits() > 0 && !ship.is_sunk())
+        {
+            weapons.push(format!(
+                "Repair x{} (repair C3, a hit on a ship that isn't sunk)",
+                self.repairs
+            ));
+        }
+        if weapons.is_empty() {
+            None
+        } else {
+            Some(format!("Weapons: {}", weapons.join(", ")))
+        }
+    }
+}
+
+impl Default for Arsenal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Grid {
+    ///
+    /// Returns the layer of this board the weapon is aimed at: the one it reaches if the
+    /// board has a deep layer, otherwise the surface
+    ///
+    fn reach(&self, weapon: Weapon) -> Layer {
+        match self.is_layered() {
+            true => weapon.layer(),
+            false => Layer::Surface,
+        }
+    }
+
+    ///
+    /// Arguements:
+    ///     weapon: Weapon - the weapon being used
+    ///     target: (i32, char) - the (row, column) it is aimed at
+    ///     direction: Direction - the way an airstrike or torpedo travels
+    /// Returns the (row, column) of every point the weapon hits on the layer it is aimed
+    /// at, see reach, in order, leaving out points that can't be guessed again.
+    /// A torpedo's path ends at the first point with a ship on it that hasn't been hit, or
+    /// a mine or decoy, which are only on the surface.
+    /// Returns an error if a torpedo doesn't start on the edge it travels away from
+    ///
+    pub(crate) fn target_points(
+        &self,
+        weapon: Weapon,
+        target: (i32, char),
+        direction: Direction,
+    ) -> Result<Vec<(i32, char)>, String> {
+        let columns: Vec<char> = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J'];
+        let (row, col) = target;
+        let (r_idx, c_idx) = get_coord_index(row, col);
+        let (r_idx, c_idx) = (r_idx as i32, c_idx as i32);
+        let (d_row, d_col) = direction.step();
+
+        let path: Vec<(i32, i32)> = match weapon {
+            Weapon::Airstrike => (0..3)
+                .map(|i| (r_idx + d_row * i, c_idx + d_col * i))
+                .collect(),
+            Weapon::Bombardment => (-1..=1)
+                .flat_map(|r| (-1..=1).map(move |c| (r_idx + r, c_idx + c)))
+                .collect(),
+            Weapon::Torpedo => {
+                let behind = (r_idx - d_row, c_idx - d_col);
+                if (0..10).contains(&behind.0) && (0..10).contains(&behind.1) {
+                    return Err(
+                        "A torpedo has to start on the edge of the board it travels away from"
+                            .to_string(),
+                    );
+                }
+                (0..10)
+                    .map(|i| (r_idx + d_row * i, c_idx + d_col * i))
+                    .collect()
+            }
+        };
+
+        let layer = self.reach(weapon);
+        let mut points = vec![];
+        for (r, c) in path {
+            if !(0..10).contains(&r) || !(0..10).contains(&c) {
+                continue;
+            }
+            let (r, c) = (r as usize, c as usize);
+            if !self.can_guess(self.grid[layer.index()][r][c].state) {
+                continue;
+            }
+            points.push((r as i32 + 1, columns[c]));
+            let trap = layer == Layer::Surface
+                && (self.mines.contains(&(r, c)) || self.decoys.contains(&(r, c)));
+            if weapon == Weapon::Torpedo && (trap || self.fleet.ship_at(layer, (r, c)).is_some()) {
+                break;
+            }
+        }
+        Ok(points)
+    }
+
+    ///
+    /// Uses a special weapon on this board, guessing every point it hits
+    /// If the board has a deep layer, the weapon is aimed at the layer it reaches
+    /// Arguements: the same as target_points
+    /// Returns every guess it made, as they were added to self.history, stopping early if
+    /// the game is over, or an error if the weapon can't be aimed there
+    ///
+    pub(crate) fn strike(
+        &mut self,
+        weapon: Weapon,
+        target: (i32, char),
+        direction: Direction,
+    ) -> Result<Vec<Shot>, String> {
+        let layer = self.reach(weapon);
+        let points = self.target_points(weapon, target, direction)?;
+        if !self.quiet {
+            println!("{weapon}!");
+        }
+        let mut shots = vec![];
+        for (row, col) in points {
+            if !self.quiet {
+                print!("{col}{row}: ");
+            }
+            let report = self.guess_layer(row, col, layer);
+            shots.push(self.history[self.history.len() - 1].clone());
+            if report.1 .0 == 'f' {
+                break;
+            }
+        }
+        Ok(shots)
+    }
+
+    ///
+    /// Makes a sonar scan of this board and prints how many ship points it found
+    /// Arguements:
+    ///     center: (i32, char) - the (row, column) the scan is aimed at
+    ///     shape: ScanShape - the area it covers
+    /// Returns the (col, row) of every point the scan covered, and the count
+    ///
+    pub(crate) fn announce_scan(
+        &self,
+        center: (i32, char),
+        shape: ScanShape,
+    ) -> (Vec<(char, i32)>, usize) {
+        let count = self.sonar_scan(center, shape);
+        if !self.quiet {
+            println!(
+                "Sonar scan ({shape}) around {}{}: {count} ship point{} found",
+                center.1,
+                center.0,
+                if count == 1 { "" } else { "s" }
+            );
+        }
+        let area = shape
+            .area(center)
+            .into_iter()
+            .map(|(row, col)| (col, row))
+            .collect();
+        (area, count)
+    }
+
+    ///
+    /// Prompts the user for a guess, a special weapon, a sonar scan, a move or a repair, in
+    /// the Advanced Mission, sonar, mobile fleet, layers or repair rules
+    /// Arguements:
+    ///     arsenal: &mut Arsenal - the user's weapons, scans, moves and repairs
+    ///     own: &mut Grid - the user's board, whose fleet decides which weapons can be used,
+    ///         which ships can move and which hits can be repaired
+    ///     deadline: &mut Option<(Instant, String)> - when the turn runs out of time and
+    ///         the guess to make for the user if it does, or None for no limit
+    /// Returns true if the game is over
+    ///
+    pub(crate) fn request_user_attack(
+        &mut self,
+        arsenal: &mut Arsenal,
+        own: &mut Grid,
+        deadline: &mut Option<(Instant, String)>,
+    ) -> bool {
+        arsenal.start_turn();
+        let fleet = own.fleet.clone();
+        if let Some(status) = arsenal.status(&fleet) {
+            println!("{status}");
+        }
+        if self.is_layered() {
+            println!(
+                "Please make a guess, with 'deep' after it for the deep layer, or use a weapon"
+            );
+        } else {
+            println!("Please make a guess or use a weapon");
+        }
+        loop {
+            match parse_attack(&read_timed_line(deadline)) {
+                Attack::Shot((row, col)) => return self.guess(row, col).1 .0 == 'f',
+                Attack::Deep((row, col)) => {
+                    if !self.is_layered() {
+                        println!("There is only a deep layer in the layers rules");
+                        continue;
+                    }
+                    return self.guess_layer(row, col, Layer::Deep).1 .0 == 'f';
+                }
+                Attack::Special(weapon, target, direction) => {
+                    if !arsenal.available(&fleet).contains(&weapon) {
+                        println!(
+                            "You can't use the {weapon}, its ship is sunk or it is out of ammo"
+                        );
+                        continue;
+                    }
+                    match self.strike(weapon, target, direction) {
+                        Ok(shots) => {
+                            arsenal.spend(weapon);
+                            return shots.iter().any(|shot| shot.report.1 .0 == 'f');
+                        }
+                        Err(e) => println!("{e}"),
+                    }
+                }
+                Attack::Scan(center, shape) => {
+                    if arsenal.scans == 0 {
+                        println!("You have no sonar scans left");
+                        continue;
+                    }
+                    arsenal.spend_scan();
+                    self.announce_scan(center, shape);
+                    return false;
+                }
+                Attack::Move(class, direction) => {
+                    if !arsenal.moves {
+                        println!("Ships can only move in the mobile fleet rules");
+                        continue;
+                    }
+                    match own.move_ship(class, direction) {
+                        Ok(()) => {
+                            println!(
+                                "Your {class} moved {}",
+                                format!("{direction:?}").to_lowercase()
+                            );
+                            return false;
+                        }
+                        Err(e) => println!("{e}"),
+                    }
+                }
+                Attack::Repair((row, col)) => {
+                    if arsenal.repairs == 0 {
+                        println!("You have no repairs left");
+                        continue;
+                    }
+                    match own.repair(row, col) {
+                        Ok(class) => {
+                            arsenal.repairs -= 1;
+                            println!("Your {class} is repaired at {col}{row}");
+                            return false;
+                        }
+                        Err(e) => println!("{e}"),
+                    }
+                }
+                Attack::Invalid => {
+                    println!("Please make a guess like A8, or use one of the options listed above")
+                }
+            }
+        }
+    }
+
+    ///
+    /// The computer's turn in the Advanced Mission or sonar rules
+    /// Arguements:
+    ///     guesser: &mut dyn Guesser - the computer opponent, which is told every result
+    ///     arsenal: &mut Arsenal - the computer's weapons and scans
+    ///     own: &Fleet - the computer's fleet
+    /// If it has a scan left and the guesser wants to scan, scans where the guesser
+    /// picks and tells it the result.
+    /// Otherwise asks the guesser for a guess, then either fires at it or uses the weapon
+    /// Arsenal::computer_attack picks. If the weapon never reaches the guess, the guesser
+    /// is given it back.
+    /// Returns true if the game is over, or the guesser's error if it has nothing left to
+    /// guess
+    ///
+    pub(crate) fn make_computer_attack(
+        &mut self,
+        guesser: &mut dyn Guesser,
+        arsenal: &mut Arsenal,
+        own: &Fleet,
+    ) -> Result<bool, String> {
+        arsenal.start_turn();
+        if arsenal.scans > 0 {
+            if let Some((col, row)) = guesser.scan_target() {
+                arsenal.spend_scan();
+                if !self.quiet {
+                    println!("The computer uses its sonar");
+                }
+                let (area, count) = self.announce_scan((row, col), ScanShape::Square);
+                guesser.record_scan(&area, count);
+                return Ok(false);
+            }
+        }
+        let (col, row) = guesser.next_guess()?;
+        let strike =
+            arsenal
+                .computer_attack(own, (col, row))
+                .and_then(|(weapon, target, direction)| {
+                    if !self.quiet {
+                        println!("The computer uses its {weapon}");
+                    }
+                    let shots = self.strike(weapon, target, direction).ok()?;
+                    arsenal.spend(weapon);
+                    Some(shots)
+                });
+        let shots = match strike {
+            Some(shots) => shots,
+            None => {
+                if !self.quiet {
+                    println!("Computer guess: {col}{row}");
+                }
+                self.guess(row, col);
+                vec![self.history[self.history.len() - 1].clone()]
+            }
+        };
+
+        for shot in &shots {
+            guesser.record_guess(shot.report, shot.column, shot.row);
+        }
+        if !shots
+            .iter()
+            .any(|shot| (shot.column, shot.row) == (col, row))
+        {
+            guesser.return_guess(col, row);
+        }
+        Ok(shots.iter().any(|shot| shot.report.1 .0 == 'f'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::possible_guess::PossibleGuesses;
+
+    #[test]
+    fn test_parse_attack() {
+        assert_eq!(parse_attack("B7\n"), Attack::Shot((7, 'B')));
+        assert_eq!(
+            parse_attack("bombard E5"),
+            Attack::Special(Weapon::Bombardment, (5, 'E'), Direction::Right)
+        );
+        assert_eq!(
+            parse_attack("Airstrike c3 down"),
+            Attack::Special(Weapon::Airstrike, (3, 'C'), Direction::Down)
+        );
+        assert_eq!(parse_attack("torpedo A5"), Attack::Invalid);
+        assert_eq!(parse_attack("nuke A5 right"), Attack::Invalid);
+        assert_eq!(
+            parse_attack("sonar E5"),
+            Attack::Scan((5, 'E'), ScanShape::Square)
+        );
+        assert_eq!(
+            parse_attack("scan J10 plus"),
+            Attack::Scan((10, 'J'), ScanShape::Plus)
+        );
+        assert_eq!(parse_attack("sonar E5 right"), Attack::Invalid);
+        assert_eq!(
+            parse_attack("move cruiser Down"),
+            Attack::Move("Cruiser", Direction::Down)
+        );
+        assert_eq!(parse_attack("move tugboat down"), Attack::Invalid);
+        assert_eq!(parse_attack("a5 deep"), Attack::Deep((5, 'A')));
+        assert_eq!(parse_attack("Repair c3"), Attack::Repair((3, 'C')));
+        assert_eq!(parse_attack("A5 surface"), Attack::Shot((5, 'A')));
+    }
+
+    #[test]
+    fn test_strike() {
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.create_ship(("Destroyer".to_string(), 2), &mut vec![(4, 4), (4, 5)])
+            .unwrap();
+
+        let bombed = grid.strike(Weapon::Bombardment, (1, 'A'), Direction::Right);
+        assert_eq!(bombed.unwrap().len(), 4);
+        let air = grid
+            .target_points(Weapon::Airstrike, (1, 'A'), Direction::Down)
+            .unwrap();
+        assert_eq!(air, vec![(3, 'A')]);
+
+        assert!(grid
+            .target_points(Weapon::Torpedo, (5, 'B'), Direction::Right)
+            .is_err());
+        let torpedo = grid
+            .strike(Weapon::Torpedo, (5, 'J'), Direction::Left)
+            .unwrap();
+        assert_eq!(torpedo.len(), 5);
+        assert_eq!((torpedo[4].column, torpedo[4].row), ('F', 5));
+        assert_eq!(torpedo[4].report, ('h', ('u', 0)));
+        assert_eq!(grid.grid[0][4][6].state, CoordState::Miss);
+
+        // a torpedo sets off the first mine or decoy in its way, and passes over a
+        // detonated mine
+        grid.mines.push((7, 6));
+        grid.decoys.push((7, 3));
+        let torpedo = grid
+            .strike(Weapon::Torpedo, (8, 'J'), Direction::Left)
+            .unwrap();
+        assert_eq!(torpedo.last().unwrap().report, ('x', ('n', 0)));
+        assert_eq!((torpedo[3].column, torpedo[3].row), ('G', 8));
+        let torpedo = grid
+            .strike(Weapon::Torpedo, (8, 'J'), Direction::Left)
+            .unwrap();
+        assert_eq!((torpedo[0].column, torpedo[0].row), ('F', 8));
+        assert_eq!(torpedo.last().unwrap().report, ('h', ('u', 0)));
+        assert_eq!((torpedo[2].column, torpedo[2].row), ('D', 8));
+    }
+
+    #[test]
+    fn test_arsenal() {
+        let mut own = Fleet::new();
+        own.add("Battleship", vec![(0, 0), (0, 1), (0, 2), (0, 3)]);
+        own.add("Submarine", vec![(2, 0), (2, 1), (2, 2)]);
+        let mut arsenal = Arsenal::new();
+        assert_eq!(
+            arsenal.available(&own),
+            vec![Weapon::Bombardment, Weapon::Torpedo]
+        );
+        assert_eq!(
+            arsenal.computer_attack(&own, ('H', 4)),
+            Some((Weapon::Bombardment, (4, 'H'), Direction::Left))
+        );
+        arsenal.spend(Weapon::Bombardment);
+        assert_eq!(
+            arsenal.computer_attack(&own, ('H', 4)),
+            Some((Weapon::Torpedo, (4, 'J'), Direction::Left))
+        );
+
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.set_computer_ships(crate::placement::PlacementStyle::Random)
+            .unwrap();
+        let mut guesser = PossibleGuesses::new();
+        assert_eq!(
+            grid.make_computer_attack(&mut guesser, &mut arsenal, &own),
+            Ok(false)
+        );
+        assert_eq!(arsenal.ammo_left(Weapon::Torpedo), 1);
+        assert!(!grid.history.is_empty());
+        let guessed: Vec<(char, i32)> = grid.history.iter().map(|s| (s.column, s.row)).collect();
+        assert!(guesser.points.iter().all(|p| !guessed.contains(p)));
+
+        let mut arsenal = Arsenal::for_rules(Rules::from_input("sonar").unwrap());
+        assert!(arsenal.available(&own).is_empty());
+        let mut grid = Grid::new();
+        grid.quiet = true;
+        grid.set_computer_ships(crate::placement::PlacementStyle::Random)
+            .unwrap();
+        let mut guesser = PossibleGuesses::new();
+        assert_eq!(
+            grid.make_computer_attack(&mut guesser, &mut arsenal, &own),
+            Ok(false)
+        );
+        assert_eq!((arsenal.scans, grid.history.len()), (0, 0));
+        assert_eq!(
+            grid.make_computer_attack(&mut guesser, &mut arsenal, &own),
+            Ok(false)
+        );
+        assert_eq!(grid.history.len(), 1);
+
+        let mut arsenal = Arsenal::for_rules(Rules::from_input("sonar=2").unwrap());
+        arsenal.spend_scan();
+        arsenal.start_turn();
+        assert_eq!(arsenal.scans, 0);
+        arsenal.start_turn();
+        assert_eq!(arsenal.scans, 1);
+    }
+}
@@ -0,0 +1,69 @@
+use battleship::{CoordState, Difficulty, Grid, Layer, Outcome, PlacementStyle, FLEET};
+
+///
+/// Places a fleet and fires at it through the library, the way a bot would
+///
+#[test]
+fn test_place_and_fire() {
+    let mut grid = Grid::new();
+    grid.quiet = true;
+
+    assert!(grid.place_ship("Destroyer", (1, 'A'), (1, 'B'), 2).is_ok());
+    assert!(grid.place_ship("Cruiser", (1, 'A'), (3, 'A'), 3).is_err());
+    assert!(grid.place_ship("Cruiser", (9, 'A'), (11, 'A'), 3).is_err());
+    assert!(grid.place_ship("Cruiser", (3, 'A'), (5, 'A'), 3).is_ok());
+    assert_eq!(grid.grid[0][0][0].state, CoordState::Ship);
+    assert!(grid.place_random_ship("Submarine", 3).is_ok());
+    assert_eq!(grid.fleet.len(), 3);
+    grid.fleet.remove("Submarine");
+
+    let surface = Layer::Surface;
+    assert_eq!(grid.fire(10, 'J', surface), Ok(Outcome::Miss));
+    assert_eq!(grid.fire(1, 'A', surface), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(1, 'B', surface), Ok(Outcome::Sunk(2)));
+    assert!(grid.fire(11, 'A', surface).is_err());
+    assert!(grid.fire(1, 'K', surface).is_err());
+    assert!(grid.fire(1, 'C', Layer::Deep).is_err());
+    assert_eq!(grid.fire(3, 'A', surface), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(4, 'A', surface), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(5, 'A', surface), Ok(Outcome::Won(3)));
+    assert_eq!(grid.history.len(), 6);
+}
+
+///
+/// Fires at a submarine on the deep layer through the library
+///
+#[test]
+fn test_fire_deep() {
+    let mut grid = Grid::new();
+    grid.quiet = true;
+    grid.add_deep_layer();
+    assert!(grid.place_ship("Destroyer", (1, 'A'), (1, 'B'), 2).is_ok());
+    assert!(grid.place_ship("Submarine", (1, 'A'), (3, 'A'), 3).is_ok());
+
+    assert_eq!(grid.fire(1, 'A', Layer::Deep), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(3, 'A', Layer::Surface), Ok(Outcome::Miss));
+    assert_eq!(grid.fire(2, 'A', Layer::Deep), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(3, 'A', Layer::Deep), Ok(Outcome::Sunk(3)));
+    assert_eq!(grid.fire(1, 'A', Layer::Surface), Ok(Outcome::Hit));
+    assert_eq!(grid.fire(1, 'B', Layer::Surface), Ok(Outcome::Won(2)));
+}
+
+///
+/// Lets a computer opponent play a whole fleet through the library
+///
+#[test]
+fn test_opponent() {
+    let mut grid = Grid::new();
+    grid.quiet = true;
+    grid.set_computer_ships(PlacementStyle::Random).unwrap();
+    assert_eq!(grid.fleet.len(), FLEET.len());
+
+    let mut guesser = Difficulty::Medium.settings().opponent();
+    let mut shots = 0;
+    while !grid.make_computer_guess(guesser.as_mut()).unwrap() {
+        shots += 1;
+        assert!(shots < 100);
+    }
+    assert!(grid.fleet.all_sunk());
+}
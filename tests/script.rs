@@ -0,0 +1,18 @@
+use std::{fs, process::Command};
+
+///
+/// Plays the scripted game in tests/scripts and checks the transcript matches the
+/// one saved next to it
+///
+#[test]
+fn test_scripted_game() {
+    let output = Command::new(env!("CARGO_BIN_EXE_battleship"))
+        .args(["--script", "tests/scripts/easy_game.txt"])
+        .output()
+        .expect("error: unable to run the game");
+    assert!(output.status.success());
+
+    let transcript = String::from_utf8(output.stdout).unwrap();
+    let expected = fs::read_to_string("tests/scripts/easy_game.out").unwrap();
+    assert_eq!(transcript, expected);
+}